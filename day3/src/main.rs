@@ -1,9 +1,8 @@
+use std::env;
 use std::fs::read_to_string;
 use std::str::FromStr;
 
 const INPUT_FILENANME: &str = "input.txt";
-static mut X_STEP: usize = 3;
-static mut Y_STEP: usize = 1;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 enum Cell {
@@ -23,6 +22,52 @@ impl FromStr for Cell {
     }
 }
 
+/// What happens when a walk leaves the pattern
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+enum Boundary {
+    /// Pattern repeats to the right and to the left, walk stops at the top or bottom edge
+    #[default]
+    WrapHorizontal,
+    /// Pattern repeats in both directions, walk stops once it comes back to the start
+    Torus,
+    /// Walk bounces off every edge like a billiard ball
+    Reflect,
+    /// Walk stops at any edge
+    Wall,
+}
+
+impl FromStr for Boundary {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "horizontal" => Ok(Boundary::WrapHorizontal),
+            "torus" => Ok(Boundary::Torus),
+            "reflect" => Ok(Boundary::Reflect),
+            "wall" => Ok(Boundary::Wall),
+            _ => Err(format!("Boundary mode is not recognized: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct Slope {
+    dx: isize,
+    dy: isize,
+}
+
+impl Slope {
+    fn new(dx: isize, dy: isize) -> Self {
+        Slope { dx, dy }
+    }
+}
+
+impl Default for Slope {
+    fn default() -> Self {
+        Slope::new(3, 1)
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 struct Forest {
     pattern: Vec<Vec<Cell>>,
@@ -33,61 +78,127 @@ impl Forest {
         Forest { pattern }
     }
 
-    fn step_and_count_trees(self) -> u32 {
-        self.into_iter().fold(0, |count, x| {
-            count
-                + match x {
-                    (Cell::Tree, _) => 1,
-                    _ => 0,
-                }
-        })
-    }
-}
-
-impl IntoIterator for Forest {
-    type Item = (Cell, (usize, usize));
-    type IntoIter = ForestIntoIterator;
-
-    fn into_iter(self) -> Self::IntoIter {
+    fn walk(self, slope: Slope, boundary: Boundary) -> ForestIntoIterator {
         let y_size = self.pattern.len();
-        let x_size = self.pattern[0].len();
-
-        let pattern_size = (x_size, y_size);
+        let x_size = self.pattern.first().map_or(0, Vec::len);
 
         ForestIntoIterator {
             forest: self,
-            pattern_size,
+            pattern_size: (x_size as isize, y_size as isize),
+            slope,
+            boundary,
             x: 0,
             y: 0,
         }
     }
+
+    fn step_and_count_trees(self, slope: Slope, boundary: Boundary) -> u64 {
+        self.walk(slope, boundary).count_trees()
+    }
+}
+
+impl IntoIterator for Forest {
+    type Item = (Cell, (isize, isize));
+    type IntoIter = ForestIntoIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.walk(Slope::default(), Boundary::default())
+    }
 }
 
 struct ForestIntoIterator {
     forest: Forest,
-    pattern_size: (usize, usize),
-    x: usize,
-    y: usize,
+    pattern_size: (isize, isize),
+    slope: Slope,
+    boundary: Boundary,
+    /// Position in the endless plane, before any wrapping or reflecting
+    x: isize,
+    y: isize,
+}
+
+/// Fold an endless coordinate into `0..size` as if it bounced off both edges
+fn reflect(position: isize, size: isize) -> isize {
+    let period = (2 * (size - 1)).max(1);
+    let position = position.rem_euclid(period);
+
+    if position < size {
+        position
+    } else {
+        period - position
+    }
+}
+
+impl ForestIntoIterator {
+    fn count_trees(self) -> u64 {
+        self.fold(0, |count, x| {
+            count
+                + match x {
+                    (Cell::Tree, _) => 1,
+                    _ => 0,
+                }
+        })
+    }
+
+    /// Cell of the pattern under the current position, `None` if the walk fell off
+    fn cell_position(&self) -> Option<(isize, isize)> {
+        let (width, height) = self.pattern_size;
+        let inside = |position: isize, size: isize| (0..size).contains(&position);
+
+        match self.boundary {
+            Boundary::WrapHorizontal if inside(self.y, height) => {
+                Some((self.x.rem_euclid(width), self.y))
+            }
+            Boundary::Torus => Some((self.x.rem_euclid(width), self.y.rem_euclid(height))),
+            Boundary::Reflect => Some((reflect(self.x, width), reflect(self.y, height))),
+            Boundary::Wall if inside(self.x, width) && inside(self.y, height) => {
+                Some((self.x, self.y))
+            }
+            _ => None,
+        }
+    }
+
+    /// Walking state reduced by the periods of the boundary mode.
+    ///
+    /// The walk is a translation on these states, so the first state that repeats is always
+    /// the starting one: seeing `(0, 0)` again means that we went around a full cycle.
+    fn cycle_state(&self) -> (isize, isize) {
+        let (width, height) = self.pattern_size;
+        let reflect_period = |size: isize| (2 * (size - 1)).max(1);
+
+        match self.boundary {
+            Boundary::WrapHorizontal => (self.x.rem_euclid(width), self.y),
+            Boundary::Torus => (self.x.rem_euclid(width), self.y.rem_euclid(height)),
+            Boundary::Reflect => (
+                self.x.rem_euclid(reflect_period(width)),
+                self.y.rem_euclid(reflect_period(height)),
+            ),
+            Boundary::Wall => (self.x, self.y),
+        }
+    }
 }
 
 impl Iterator for ForestIntoIterator {
-    type Item = (Cell, (usize, usize));
+    type Item = (Cell, (isize, isize));
 
     fn next(&mut self) -> Option<Self::Item> {
-        unsafe {
-            self.x += X_STEP;
-            self.y += Y_STEP;
+        if self.pattern_size.0 == 0 || self.pattern_size.1 == 0 {
+            return None;
         }
 
-        match self
-            .forest
-            .pattern
-            // We can iterate right forever, but not down
-            .get(self.y)
-            .and_then(|line| line.get(self.x % self.pattern_size.0).cloned())
-        {
-            Some(cell) => Some((cell, (self.x, self.y))),
-            None => None,
+        self.x += self.slope.dx;
+        self.y += self.slope.dy;
+
+        if self.cycle_state() == (0, 0) {
+            return None;
+        }
+
+        let (x, y) = self.cell_position()?;
+        let cell = self.forest.pattern[y as usize].get(x as usize).cloned()?;
+
+        // Wrapping modes walk an endless plane, so report where we are in it
+        match self.boundary {
+            Boundary::Reflect => Some((cell, (x, y))),
+            _ => Some((cell, (self.x, self.y))),
         }
     }
 }
@@ -110,6 +221,8 @@ impl FromStr for Forest {
 }
 
 #[cfg(test)]
+// read_input and main stay at the end of the file, after the tests
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use indoc::indoc;
     use pretty_assertions::assert_eq;
@@ -162,7 +275,7 @@ mod tests {
 
         let forest = Forest::from_str(map).unwrap();
 
-        let answer: Vec<(Cell, (usize, usize))> = forest.into_iter().collect();
+        let answer: Vec<(Cell, (isize, isize))> = forest.into_iter().collect();
 
         assert_eq!(answer, correct_answer);
 
@@ -208,7 +321,64 @@ mod tests {
 
         let forest = Forest::from_str(map).unwrap();
 
-        assert_eq!(forest.step_and_count_trees(), 7);
+        assert_eq!(
+            forest.step_and_count_trees(Slope::default(), Boundary::default()),
+            7
+        );
+    }
+
+    #[test]
+    fn test_walk_boundaries() {
+        let map = indoc! {"
+            #..#
+            .#..
+            ..#.
+        "};
+        let forest = Forest::from_str(map).unwrap();
+        let walk = |dx, dy, boundary| forest.clone().walk(Slope::new(dx, dy), boundary);
+
+        assert_eq!(
+            walk(-1, 1, Boundary::WrapHorizontal).collect::<Vec<_>>(),
+            vec![(Free, (-1, 1)), (Tree, (-2, 2))]
+        );
+        assert_eq!(walk(1, -1, Boundary::WrapHorizontal).count(), 0);
+
+        assert_eq!(
+            walk(1, 1, Boundary::Wall).collect::<Vec<_>>(),
+            vec![(Tree, (1, 1)), (Tree, (2, 2))]
+        );
+        assert_eq!(walk(-1, 1, Boundary::Wall).count(), 0);
+
+        // 4 columns and 3 rows come back to the start after 12 steps
+        assert_eq!(walk(1, 1, Boundary::Torus).count(), 11);
+        assert_eq!(walk(1, 1, Boundary::Torus).count_trees(), 3);
+        assert_eq!(walk(-1, -1, Boundary::Torus).count(), 11);
+
+        assert_eq!(
+            walk(1, 1, Boundary::Reflect).take(4).collect::<Vec<_>>(),
+            vec![
+                (Tree, (1, 1)),
+                (Tree, (2, 2)),
+                (Free, (3, 1)),
+                (Free, (2, 0)),
+            ]
+        );
+        assert_eq!(walk(1, 1, Boundary::Reflect).count(), 11);
+
+        for &boundary in &[
+            Boundary::WrapHorizontal,
+            Boundary::Torus,
+            Boundary::Reflect,
+            Boundary::Wall,
+        ] {
+            assert_eq!(
+                walk(0, 0, boundary).count(),
+                0,
+                "{:?} never stops",
+                boundary
+            );
+        }
+        assert_eq!(walk(1, 0, Boundary::WrapHorizontal).count(), 3);
     }
 
     #[test]
@@ -249,14 +419,14 @@ mod tests {
     /// Find positions of all `O`s and `X`s in __full__ map
     fn find_correct_positions_in_full_map_no_iterators(
         full_map: &str,
-    ) -> Vec<(Cell, (usize, usize))> {
-        let mut positions: Vec<(Cell, (usize, usize))> = vec![];
+    ) -> Vec<(Cell, (isize, isize))> {
+        let mut positions: Vec<(Cell, (isize, isize))> = vec![];
 
         for (y, line) in full_map.split_whitespace().enumerate() {
             for (x, chr) in line.chars().enumerate() {
                 match chr {
-                    'O' => positions.push((Free, (x, y))),
-                    'X' => positions.push((Tree, (x, y))),
+                    'O' => positions.push((Free, (x as isize, y as isize))),
+                    'X' => positions.push((Tree, (x as isize, y as isize))),
                     _ => {}
                 }
             }
@@ -266,7 +436,7 @@ mod tests {
     }
 
     /// Then I though I may practice in iterators for some time...
-    fn find_correct_positions_in_full_map(full_map: &str) -> Vec<(Cell, (usize, usize))> {
+    fn find_correct_positions_in_full_map(full_map: &str) -> Vec<(Cell, (isize, isize))> {
         full_map
             .split_whitespace()
             .enumerate()
@@ -274,11 +444,11 @@ mod tests {
                 line.chars()
                     .enumerate()
                     .filter_map(|(x, chr)| match chr {
-                        'O' => Some((Free, (x, y))),
-                        'X' => Some((Tree, (x, y))),
+                        'O' => Some((Free, (x as isize, y as isize))),
+                        'X' => Some((Tree, (x as isize, y as isize))),
                         _ => None,
                     })
-                    .collect::<Vec<(Cell, (usize, usize))>>()
+                    .collect::<Vec<(Cell, (isize, isize))>>()
             })
             .collect()
     }
@@ -289,22 +459,27 @@ fn read_input() -> Forest {
 }
 
 fn main() {
+    let boundary: Boundary = env::args()
+        .nth(1)
+        .map(|mode| mode.parse().unwrap())
+        .unwrap_or_default();
+
     let forest = read_input();
     println!(
         "Trees encountered: {}",
-        forest.clone().step_and_count_trees()
+        forest
+            .clone()
+            .step_and_count_trees(Slope::default(), boundary)
     );
 
     println!(
         "Trees encountered in every slope: {}",
-        &[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
-            .into_iter()
-            .fold(1, |acc, &(x, y)| {
-                unsafe {
-                    X_STEP = x;
-                    Y_STEP = y;
-                }
-                acc * forest.clone().step_and_count_trees()
+        [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
+            .iter()
+            .fold(1, |acc, &(dx, dy)| {
+                acc * forest
+                    .clone()
+                    .step_and_count_trees(Slope::new(dx, dy), boundary)
             })
     );
 }
@@ -0,0 +1,39 @@
+#![no_main]
+// Only parsing and validation are exercised, the rest of the modules is unused here
+#![allow(dead_code)]
+
+use libfuzzer_sys::fuzz_target;
+
+// day4 is a binary, so the parser modules are compiled right into the fuzz target
+#[path = "../../src/report.rs"]
+mod report;
+#[path = "../../src/schema.rs"]
+mod schema;
+#[path = "../../src/typed.rs"]
+mod typed;
+#[path = "../../src/types.rs"]
+mod types;
+
+use types::{Field, Passport};
+
+fuzz_target!(|data: &[u8]| {
+    let s = match std::str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    for entry in s.split_whitespace() {
+        if let Ok(field) = entry.parse::<Field>() {
+            assert_eq!(field.to_string().parse::<Field>(), Ok(field));
+        }
+    }
+
+    if let Ok(passport) = s.parse::<Passport>() {
+        // Converts the passport to `ValidPassport`, checking every value
+        let _ = passport.is_valid_2();
+        // Rejected repeated entries are not written back
+        if passport.repeated().next().is_none() {
+            assert_eq!(passport.to_string().parse::<Passport>(), Ok(passport));
+        }
+    }
+});
@@ -0,0 +1,268 @@
+use crate::types::{Field, FieldKind, Passport};
+use serde::{de::Error as _, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+use std::{collections::BTreeMap, str::FromStr};
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            case => Err(format!("Export format is not recognized: {}", case)),
+        }
+    }
+}
+
+/// Key of the rejected repeated kinds, not a field of the passport
+const REPEATED: &str = "repeated";
+
+/// Example: {"byr": "1937", "ecl": "gry"}, rejected repeated kinds go to
+/// {"byr": "1937", "repeated": ["byr"]}
+impl Serialize for Passport {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        for field in self.fields() {
+            map.serialize_entry(field.key(), field.value())?;
+        }
+        let repeated: Vec<&str> = self.repeated().map(FieldKind::key).collect();
+        if !repeated.is_empty() {
+            map.serialize_entry(REPEATED, &repeated)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(Deserialize)]
+struct Entries {
+    #[serde(default)]
+    repeated: Vec<String>,
+    #[serde(flatten)]
+    fields: BTreeMap<String, String>,
+}
+
+impl<'de> Deserialize<'de> for Passport {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = Entries::deserialize(deserializer)?;
+        let repeated = entries
+            .repeated
+            .iter()
+            .map(|key| FieldKind::from_key(key))
+            .collect::<Result<Vec<FieldKind>, String>>()
+            .map_err(D::Error::custom)?;
+
+        entries
+            .fields
+            .into_iter()
+            .map(|(key, value)| format!("{}:{}", key, value).parse::<Field>())
+            .collect::<Result<Passport, String>>()
+            .map(|passport| passport.with_repeated(repeated))
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Passport with the results of both validation rules
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Exported {
+    #[serde(flatten)]
+    pub passport: Passport,
+    /// Recalculated on import, so the columns may be left out
+    #[serde(default)]
+    pub valid_1: bool,
+    #[serde(default)]
+    pub valid_2: bool,
+}
+
+impl From<Passport> for Exported {
+    fn from(passport: Passport) -> Self {
+        Exported {
+            valid_1: passport.is_valid_1(),
+            valid_2: passport.is_valid_2(),
+            passport,
+        }
+    }
+}
+
+/// Quote the value if it would break the row
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub(crate) fn to_csv(passports: &[Exported]) -> String {
+    // Columns go in the order of field kinds
+    let header = FieldKind::iter()
+        .map(FieldKind::key)
+        .chain(vec!["valid_1", "valid_2"])
+        .collect::<Vec<&str>>()
+        .join(",");
+
+    let rows = passports.iter().map(|exported| {
+        FieldKind::iter()
+            .map(|kind| {
+                exported
+                    .passport
+                    .get(kind)
+                    .map_or(String::new(), |field| csv_escape(field.value()))
+            })
+            .chain(vec![
+                exported.valid_1.to_string(),
+                exported.valid_2.to_string(),
+            ])
+            .collect::<Vec<String>>()
+            .join(",")
+    });
+
+    std::iter::once(header)
+        .chain(rows)
+        .map(|row| row + "\n")
+        .collect()
+}
+
+pub(crate) fn to_json(passports: &[Exported]) -> String {
+    serde_json::to_string_pretty(passports).expect("Passports are always serializable") + "\n"
+}
+
+/// Read back what `to_json` wrote, validity is calculated again
+pub(crate) fn from_json(s: &str) -> Result<Vec<Exported>, String> {
+    serde_json::from_str::<Vec<Exported>>(s)
+        .map(|passports| {
+            passports
+                .into_iter()
+                .map(|exported| Exported::from(exported.passport))
+                .collect()
+        })
+        .map_err(|err| format!("Can not import passports: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DuplicatePolicy;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    fn passports() -> Vec<Exported> {
+        vec![
+            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd byr:1937 iyr:2017 cid:147 hgt:183cm",
+            "iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884 hcl:#cfa07d byr:1929",
+            "hcl:#ae17e1 iyr:2013 eyr:2024 ecl:brn pid:760753108 byr:1931 hgt:179in",
+        ]
+        .into_iter()
+        .map(|passport| Exported::from(passport.parse::<Passport>().unwrap()))
+        .collect()
+    }
+
+    #[test]
+    fn test_to_csv() {
+        assert_eq!(
+            to_csv(&passports()),
+            indoc! {"
+                byr,iyr,eyr,hgt,hcl,ecl,pid,cid,valid_1,valid_2
+                1937,2017,2020,183cm,#fffffd,gry,860033327,147,true,true
+                1929,2013,2023,,#cfa07d,amb,028048884,350,false,false
+                1931,2013,2024,179in,#ae17e1,brn,760753108,,true,false
+            "}
+        );
+        assert_eq!(csv_escape("a,\"b\""), "\"a,\"\"b\"\"\"");
+    }
+
+    #[test]
+    fn test_to_json() {
+        let json = to_json(&passports()[1..2]);
+
+        assert_eq!(
+            json,
+            indoc! {r##"
+                [
+                  {
+                    "byr": "1929",
+                    "iyr": "2013",
+                    "eyr": "2023",
+                    "hcl": "#cfa07d",
+                    "ecl": "amb",
+                    "pid": "028048884",
+                    "cid": "350",
+                    "valid_1": false,
+                    "valid_2": false
+                  }
+                ]
+            "##}
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let passports = passports();
+
+        assert_eq!(from_json(&to_json(&passports)), Ok(passports));
+    }
+
+    #[test]
+    fn test_rejected_passport_round_trip() {
+        let (passport, _) = Passport::parse_with(
+            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd byr:1990 iyr:2017 hgt:183cm byr:1991",
+            DuplicatePolicy::Reject,
+        )
+        .unwrap();
+        let passports = vec![Exported::from(passport)];
+
+        assert!(!passports[0].valid_1 && !passports[0].valid_2);
+        assert!(to_json(&passports).contains(r#""repeated": ["#));
+        assert_eq!(from_json(&to_json(&passports)), Ok(passports));
+    }
+
+    #[test]
+    fn test_from_json() {
+        // Validity columns are not trusted
+        assert_eq!(
+            from_json(r#"[{"byr": "1937", "valid_1": true, "valid_2": true}]"#),
+            Ok(vec![Exported::from(
+                "byr:1937".parse::<Passport>().unwrap()
+            )])
+        );
+        assert_eq!(
+            from_json(r#"[{"cfg": "1"}]"#),
+            Err(
+                "Can not import passports: Field key is not recognized: cfg at line 1 column 13"
+                    .into()
+            )
+        );
+    }
+
+    mod proptests {
+        use super::*;
+        use crate::types::strategies;
+        use proptest::prelude::*;
+
+        /// Passport that may have rejected repeated kinds, text passports never carry them
+        fn rejected_passport() -> impl Strategy<Value = Passport> {
+            let kinds: Vec<FieldKind> = FieldKind::iter().collect();
+
+            (
+                strategies::passport(),
+                prop::collection::vec(prop::sample::select(kinds), 0..2),
+            )
+                .prop_map(|(passport, repeated)| passport.with_repeated(repeated))
+        }
+
+        proptest! {
+            #[test]
+            fn json_round_trip(passports in prop::collection::vec(rejected_passport(), 0..5)) {
+                let exported: Vec<Exported> = passports.into_iter().map(Exported::from).collect();
+
+                prop_assert_eq!(from_json(&to_json(&exported)), Ok(exported));
+            }
+        }
+    }
+}
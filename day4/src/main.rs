@@ -1,7 +1,148 @@
+mod export;
+mod normalize;
+mod quarantine;
+mod records;
+mod report;
+mod rules;
+mod schema;
+mod stream;
+mod typed;
 mod types;
 
-use crate::types::Passport;
-use std::fs::read_to_string;
+use crate::export::{Exported, Format};
+use crate::normalize::{NormalizeMode, Normalizer};
+use crate::records::{Record, Records};
+use crate::report::{Report, Summary};
+use crate::rules::Rules;
+use crate::schema::{Document, Schema};
+use crate::stream::Checker;
+use crate::typed::ValidPassport;
+use crate::types::{DuplicatePolicy, Passport};
+use std::convert::TryFrom;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::process;
+use std::thread;
+use std::time::Instant;
+
+const INPUT_FILENAME: &str = "input.txt";
+const QUARANTINE_FILENAME: &str = "quarantine.txt";
+
+/// Input repeats of the benchmark, when not given
+const BENCH_REPEAT: usize = 100;
+
+const USAGE: &str =
+    "Usage: day4 [export <json|csv> [output file] | import <json file> | bench [repeat]] \
+                     [--input <file|->] [--jobs <threads>] [--schema <schema file>] \
+                     [--duplicates reject|keep-first|keep-last] [--normalize suggest|apply] \
+                     [--lenient] [--rules] [--report]";
+
+#[derive(Debug, PartialEq, Default)]
+enum Command {
+    /// Count valid passports from the input
+    #[default]
+    Count,
+    /// Write passports from the input to the file, or to stdout
+    Export(Format, Option<String>),
+    /// Count valid passports from exported JSON
+    Import(String),
+    /// Measure validation throughput of the input repeated many times
+    Bench(usize),
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    command: Command,
+    /// Passport file, `-` is stdin
+    input: Option<String>,
+    /// Validation threads, less than two -- validate on the main thread
+    jobs: usize,
+    schema: Option<String>,
+    duplicates: DuplicatePolicy,
+    normalize: Option<NormalizeMode>,
+    /// Put bad records to quarantine instead of failing the whole batch
+    lenient: bool,
+    /// Check cross-field rules of passports, a broken rule makes the passport invalid
+    rules: bool,
+    report: bool,
+}
+
+impl Options {
+    fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter().peekable();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "export" if options.command == Command::Count => {
+                    let format = args.next().ok_or(USAGE)?.parse()?;
+                    let output = match args.peek() {
+                        Some(output) if !output.starts_with("--") => args.next(),
+                        _ => None,
+                    };
+                    options.command = Command::Export(format, output);
+                }
+                "import" if options.command == Command::Count => {
+                    options.command = Command::Import(args.next().ok_or(USAGE)?)
+                }
+                "bench" if options.command == Command::Count => {
+                    let repeat = match args.peek() {
+                        Some(repeat) if !repeat.starts_with("--") => args
+                            .next()
+                            .and_then(|repeat| repeat.parse().ok())
+                            .ok_or(USAGE)?,
+                        _ => BENCH_REPEAT,
+                    };
+                    options.command = Command::Bench(repeat);
+                }
+                "--input" => options.input = Some(args.next().ok_or(USAGE)?),
+                "--jobs" => {
+                    options.jobs = args
+                        .next()
+                        .and_then(|jobs| jobs.parse().ok())
+                        .ok_or(USAGE)?
+                }
+                "--schema" => options.schema = Some(args.next().ok_or(USAGE)?),
+                "--duplicates" => options.duplicates = args.next().ok_or(USAGE)?.parse()?,
+                "--normalize" => options.normalize = Some(args.next().ok_or(USAGE)?.parse()?),
+                "--lenient" => options.lenient = true,
+                "--rules" => options.rules = true,
+                "--report" => options.report = true,
+                _ => return Err(USAGE.into()),
+            }
+        }
+
+        // Schema documents are only counted, and cross-field rules are only checked by the stream
+        if options.schema.is_some() && options.command != Command::Count {
+            return Err(
+                "Option --schema only counts documents, it can not export, import or bench".into(),
+            );
+        }
+        if options.rules
+            && (options.schema.is_some()
+                || matches!(options.command, Command::Export(..) | Command::Import(_)))
+        {
+            return Err("Option --rules can not be used with --schema, export or import".into());
+        }
+
+        Ok(options)
+    }
+
+    fn input(&self) -> &str {
+        self.input.as_deref().unwrap_or(INPUT_FILENAME)
+    }
+
+    fn checker(&self) -> Checker {
+        Checker {
+            duplicates: self.duplicates,
+            normalize: self.normalize,
+            lenient: self.lenient,
+            report: self.report,
+            rules: self.rules,
+        }
+    }
+}
 
 fn count_valid_passports_1(passports: &[Passport]) -> usize {
     passports
@@ -13,23 +154,272 @@ fn count_valid_passports_1(passports: &[Passport]) -> usize {
 fn count_valid_passports_2(passports: &[Passport]) -> usize {
     passports
         .iter()
-        .filter(|passport| passport.is_valid_2())
+        .filter_map(|passport| ValidPassport::try_from(passport).ok())
         .count()
 }
 
-fn read_input(filename: String) -> Vec<Passport> {
-    read_to_string(filename)
-        .expect("No file named \"input.txt\" found")
-        .split("\n\n")
-        .map(|passport_as_string| passport_as_string.parse().expect("Passport entry is bad"))
+/// Print what the normalizer would change to stderr, repair records if asked to
+fn normalize_records(
+    records: Vec<Record<String>>,
+    normalize: Normalizer,
+    mode: NormalizeMode,
+) -> Vec<Record<String>> {
+    records
+        .into_iter()
+        .map(|record| {
+            let (record, messages) = mode.normalize_record(record, normalize);
+            for message in messages {
+                eprintln!("{}", message);
+            }
+            record
+        })
         .collect()
 }
 
-fn main() {
-    let passports: Vec<Passport> = read_input("input.txt".to_string());
-    let answer_1 = count_valid_passports_1(&passports);
+/// `-` is stdin
+fn open_input(filename: &str) -> Result<Box<dyn BufRead + Send>, String> {
+    if filename == "-" {
+        return Ok(Box::new(BufReader::new(io::stdin())));
+    }
+
+    File::open(filename)
+        .map(|file| Box::new(BufReader::new(file)) as Box<dyn BufRead + Send>)
+        .map_err(|err| format!("Can not open {}: {}", filename, err))
+}
+
+fn read_input<T>(
+    filename: &str,
+    options: &Options,
+    normalize: Normalizer,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<Vec<Record<T>>, String> {
+    let mut records = Records::new(open_input(filename)?)
+        .collect::<io::Result<Vec<Record<String>>>>()
+        .map_err(|err| format!("Can not read {}: {}", filename, err))?;
+
+    if let Some(mode) = options.normalize {
+        records = normalize_records(records, normalize, mode);
+    }
+
+    if !options.lenient {
+        return records
+            .into_iter()
+            .map(|record| record.try_map(|text| parse(&text)))
+            .collect();
+    }
+
+    let (parsed, quarantined) = quarantine::partition(records, parse);
+    // Written on every lenient run, so the file never has records of an earlier one
+    quarantine::write(QUARANTINE_FILENAME, &quarantined)
+        .map_err(|err| format!("Can not write {}: {}", QUARANTINE_FILENAME, err))?;
+    if !quarantined.is_empty() {
+        eprintln!(
+            "Quarantined records: {}, see {}",
+            quarantined.len(),
+            QUARANTINE_FILENAME
+        );
+    }
+
+    Ok(parsed)
+}
+
+/// Print issues of every invalid document and the table of issues per field
+fn print_report(schema: &Schema, reports: &[Record<Report>]) {
+    for report in reports {
+        if !report.value.is_valid_2() {
+            println!("Line {}: {}", report.line, report.value);
+        }
+    }
+
+    println!(
+        "{}",
+        Summary::new(schema, reports.iter().map(|report| &report.value))
+    );
+}
+
+/// Stream passports through the validator, messages of every record are printed as they come
+fn validate_passports(options: &Options) -> Result<(), String> {
+    let filename = options.input();
+    // Only lenient runs touch the quarantine file
+    let mut quarantine = if options.lenient {
+        quarantine::Writer::create(QUARANTINE_FILENAME)
+            .map_err(|err| format!("Can not write {}: {}", QUARANTINE_FILENAME, err))?
+    } else {
+        quarantine::Writer::new(io::sink())
+    };
+    let mut output = BufWriter::new(io::stdout());
+
+    let tally = stream::validate(
+        Records::new(open_input(filename)?),
+        filename,
+        &options.checker(),
+        options.jobs,
+        &mut quarantine,
+        &mut output,
+    )?;
+    output
+        .flush()
+        .map_err(|err| format!("Can not write output: {}", err))?;
+    quarantine
+        .finish()
+        .map_err(|err| format!("Can not write {}: {}", QUARANTINE_FILENAME, err))?;
+
+    if tally.quarantined > 0 {
+        eprintln!(
+            "Quarantined records: {}, see {}",
+            tally.quarantined, QUARANTINE_FILENAME
+        );
+    }
+
+    if options.report {
+        println!("{}", tally.summary);
+        if options.rules {
+            for (rule, count) in Rules::passport().0.iter().zip(&tally.broken_rules) {
+                println!("Rule {} broken: {}", rule, count);
+            }
+        }
+    } else {
+        println!("Total valid passports 1: {}", tally.summary.valid_1);
+        println!("Total valid passports 2: {}", tally.summary.valid_2);
+    }
+
+    Ok(())
+}
+
+/// Validate the input repeated `repeat` times, once for every number of jobs up to the number of CPUs
+fn bench(options: &Options, repeat: usize) -> Result<(), String> {
+    let filename = options.input();
+    let mut text = String::new();
+    open_input(filename)?
+        .read_to_string(&mut text)
+        .map_err(|err| format!("Can not read {}: {}", filename, err))?;
+    let text = vec![text.trim_end(); repeat].join("\n\n");
+
+    let cpus = thread::available_parallelism().map_or(1, usize::from);
+    let mut jobs = vec![1];
+    while jobs.last() < Some(&cpus) {
+        jobs.push((jobs.last().unwrap() * 2).min(cpus));
+    }
+
+    for jobs in jobs {
+        let start = Instant::now();
+        let tally = stream::validate(
+            Records::new(text.as_bytes()),
+            filename,
+            &options.checker(),
+            jobs,
+            // Benchmark never touches the quarantine file
+            &mut quarantine::Writer::new(io::sink()),
+            &mut io::sink(),
+        )?;
+        let seconds = start.elapsed().as_secs_f64();
+
+        println!(
+            "Jobs {:>3}: {} records in {:.3}s, {:.0} records/s, {:.1} MiB/s",
+            jobs,
+            tally.summary.total,
+            seconds,
+            tally.summary.total as f64 / seconds,
+            text.len() as f64 / seconds / (1024.0 * 1024.0)
+        );
+    }
+
+    Ok(())
+}
+
+/// Validate documents of any type against the field definitions from `schema_filename`
+fn validate_with_schema(schema_filename: &str, options: &Options) -> Result<(), String> {
+    let schema = Schema::from_file(schema_filename)?;
+    let documents = read_input(options.input(), options, normalize::normalize, |entry| {
+        Document::parse_with(entry, options.duplicates)
+    })?;
+
+    if options.report {
+        let reports: Vec<Record<Report>> = documents
+            .iter()
+            .map(|document| Record {
+                line: document.line,
+                value: document.value.report(&schema),
+            })
+            .collect();
+        print_report(&schema, &reports);
+        return Ok(());
+    }
+
+    let answer_1 = documents
+        .iter()
+        .filter(|document| document.value.is_valid_1(&schema))
+        .count();
+    println!("Total valid documents 1: {}", &answer_1);
+
+    let answer_2 = documents
+        .iter()
+        .filter(|document| document.value.is_valid_2(&schema))
+        .count();
+    println!("Total valid documents 2: {}", &answer_2);
+
+    Ok(())
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    if let Some(schema_filename) = &options.schema {
+        return validate_with_schema(schema_filename, options);
+    }
+
+    match &options.command {
+        Command::Count => validate_passports(options),
+        Command::Bench(repeat) => bench(options, *repeat),
+        Command::Import(filename) => {
+            let text = fs::read_to_string(filename)
+                .map_err(|err| format!("Can not open {}: {}", filename, err))?;
+            let passports: Vec<Passport> = export::from_json(&text)?
+                .into_iter()
+                .map(|exported| exported.passport)
+                .collect();
+
+            print_counts(&passports);
+            Ok(())
+        }
+        Command::Export(format, output) => {
+            let exported: Vec<Exported> = read_input(
+                options.input(),
+                options,
+                normalize::normalize_passport,
+                |entry| Passport::parse_with(entry, options.duplicates),
+            )?
+            .into_iter()
+            .map(|record| Exported::from(record.value.0))
+            .collect();
+            let text = match format {
+                Format::Json => export::to_json(&exported),
+                Format::Csv => export::to_csv(&exported),
+            };
+
+            match output {
+                Some(filename) => fs::write(filename, text)
+                    .map_err(|err| format!("Can not write {}: {}", filename, err)),
+                None => {
+                    print!("{}", text);
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+fn print_counts(passports: &[Passport]) {
+    let answer_1 = count_valid_passports_1(passports);
     println!("Total valid passports 1: {}", &answer_1);
 
-    let answer_2 = count_valid_passports_2(&passports);
+    let answer_2 = count_valid_passports_2(passports);
     println!("Total valid passports 2: {}", &answer_2);
 }
+
+fn main() {
+    let result = Options::from_args(env::args().skip(1)).and_then(|options| run(&options));
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::records::Record;
+use crate::types::Field;
+use std::{fmt, str::FromStr};
+
+/// What to do with the repairs that the normalizer finds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NormalizeMode {
+    /// Only print repairs, records are parsed as they are
+    Suggest,
+    /// Print repairs and parse repaired records
+    Apply,
+}
+
+impl FromStr for NormalizeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "suggest" => Ok(NormalizeMode::Suggest),
+            "apply" => Ok(NormalizeMode::Apply),
+            case => Err(format!("Normalize mode is not recognized: {}", case)),
+        }
+    }
+}
+
+/// Normalizer of records, `normalize_passport` only for passports
+pub(crate) type Normalizer = fn(&str) -> (String, Vec<Repair>);
+
+impl NormalizeMode {
+    /// Record to parse in this mode and a "Line N: ..." message for every repair `normalize` finds
+    pub fn normalize_record(
+        self,
+        record: Record<String>,
+        normalize: Normalizer,
+    ) -> (Record<String>, Vec<String>) {
+        let (repaired, repairs) = normalize(&record.value);
+        let messages = repairs
+            .iter()
+            .map(|repair| format!("Line {}: {}", record.line + repair.line_offset, repair))
+            .collect();
+
+        match self {
+            NormalizeMode::Suggest => (record, messages),
+            NormalizeMode::Apply => (
+                Record {
+                    line: record.line,
+                    value: repaired,
+                },
+                messages,
+            ),
+        }
+    }
+}
+
+/// Field entry that the normalizer has changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Repair {
+    /// Line of the entry, counting from the first line of the record
+    pub line_offset: usize,
+    pub before: String,
+    pub after: String,
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} -> {:?}", self.before, self.after)
+    }
+}
+
+const HEIGHT_UNITS: &[(&str, &str)] = &[
+    ("centimeters", "cm"),
+    ("centimetres", "cm"),
+    ("cms", "cm"),
+    ("inches", "in"),
+    ("inch", "in"),
+    ("\"", "in"),
+];
+
+const EYE_COLORS: &[(&str, &str)] = &[
+    ("amber", "amb"),
+    ("blue", "blu"),
+    ("brown", "brn"),
+    ("gray", "gry"),
+    ("grey", "gry"),
+    ("green", "grn"),
+    ("hazel", "hzl"),
+    ("other", "oth"),
+];
+
+/// Example: Field::Height("183CM") -> Field::Height("183cm")
+fn repair_field(field: Field) -> Field {
+    match field {
+        Field::Height(value) => {
+            let value = value.to_lowercase();
+            let value = HEIGHT_UNITS
+                .iter()
+                .find_map(|(alias, unit)| {
+                    value
+                        .strip_suffix(alias)
+                        .map(|number| format!("{}{}", number, unit))
+                })
+                .unwrap_or(value);
+            Field::Height(value)
+        }
+        Field::HairColor(value) => Field::HairColor(value.to_lowercase()),
+        Field::EyeColor(value) => {
+            let value = value.to_lowercase();
+            let value = EYE_COLORS
+                .iter()
+                .find(|(synonym, _)| *synonym == value)
+                .map_or(value, |(_, color)| color.to_string());
+            Field::EyeColor(value)
+        }
+        field => field,
+    }
+}
+
+/// Example: "PID:012 345 678" -> "pid:012345678"
+fn normalize_entry(entry: &str) -> String {
+    match entry.split(':').collect::<Vec<&str>>()[..] {
+        [key, value] => {
+            let key = key.trim().to_lowercase();
+            let value: String = value.split_whitespace().collect();
+            format!("{}:{}", key, value)
+        }
+        _ => entry.to_string(),
+    }
+}
+
+/// Example: "HGT:183 CM" -> "hgt:183cm", entries that are not passport fields only get `normalize_entry`
+fn normalize_passport_entry(entry: &str) -> String {
+    let entry = normalize_entry(entry);
+
+    match entry.parse::<Field>() {
+        Ok(field) => repair_field(field).to_string(),
+        Err(_) => entry,
+    }
+}
+
+/// Fix every entry of the record with `repair`, lines of the record stay where they were
+fn normalize_with(record: &str, repair: impl Fn(&str) -> String) -> (String, Vec<Repair>) {
+    let mut repairs = vec![];
+
+    let lines: Vec<String> = record
+        .lines()
+        .enumerate()
+        .map(|(line_offset, line)| {
+            // Words without a key belong to the value before them: "hgt:183 cm"
+            let mut entries: Vec<String> = vec![];
+            for word in line.split_whitespace() {
+                match entries.last_mut() {
+                    Some(entry) if !word.contains(':') => {
+                        entry.push(' ');
+                        entry.push_str(word);
+                    }
+                    _ => entries.push(word.to_string()),
+                }
+            }
+
+            let entries: Vec<String> = entries
+                .into_iter()
+                .map(|before| {
+                    let after = repair(&before);
+                    if after != before {
+                        repairs.push(Repair {
+                            line_offset,
+                            before,
+                            after: after.clone(),
+                        });
+                    }
+                    after
+                })
+                .collect();
+
+            entries.join(" ")
+        })
+        .collect();
+
+    (lines.join("\n"), repairs)
+}
+
+/// Fix formatting of every entry of a document of any schema, e.g. "NAME:lain" or "pid:012 345 678".
+///
+/// Returns the repaired record and the list of changes.
+pub(crate) fn normalize(record: &str) -> (String, Vec<Repair>) {
+    normalize_with(record, normalize_entry)
+}
+
+/// Same as `normalize`, then the values of passport fields are repaired as well,
+/// e.g. "hgt:183 CM" or "ecl:Blue"
+pub(crate) fn normalize_passport(record: &str) -> (String, Vec<Repair>) {
+    normalize_with(record, normalize_passport_entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_normalize_entry() {
+        let cases = &[
+            ("NAME:lain", "name:lain"),
+            ("pid:012 345 678", "pid:012345678"),
+            ("hgt:183 CM", "hgt:183CM"),
+            ("ecl:Blue", "ecl:Blue"),
+            ("haha", "haha"),
+        ];
+
+        for case in cases {
+            assert_eq!(normalize_entry(case.0), case.1);
+        }
+    }
+
+    #[test]
+    fn test_normalize_passport_entry() {
+        let cases = &[
+            ("HCL:#ABCDEF", "hcl:#abcdef"),
+            ("hgt:183 cm", "hgt:183cm"),
+            ("hgt:183CM", "hgt:183cm"),
+            ("hgt:74 inches", "hgt:74in"),
+            ("hgt:74\"", "hgt:74in"),
+            ("ecl:Blue", "ecl:blu"),
+            ("Ecl:GREY", "ecl:gry"),
+            ("ecl:gry", "ecl:gry"),
+            ("pid:012 345 678", "pid:012345678"),
+            ("cid:Russia", "cid:Russia"),
+            ("haha", "haha"),
+        ];
+
+        for case in cases {
+            assert_eq!(normalize_passport_entry(case.0), case.1);
+        }
+    }
+
+    #[test]
+    fn test_normalize_record() {
+        let (record, repairs) = normalize_passport("HCL:#ABCDEF hgt:183 cm\necl:Blue  byr:1937");
+
+        assert_eq!(record, "hcl:#abcdef hgt:183cm\necl:blu byr:1937");
+        assert_eq!(
+            repairs,
+            vec![
+                Repair {
+                    line_offset: 0,
+                    before: "HCL:#ABCDEF".into(),
+                    after: "hcl:#abcdef".into(),
+                },
+                Repair {
+                    line_offset: 0,
+                    before: "hgt:183 cm".into(),
+                    after: "hgt:183cm".into(),
+                },
+                Repair {
+                    line_offset: 1,
+                    before: "ecl:Blue".into(),
+                    after: "ecl:blu".into(),
+                },
+            ]
+        );
+        assert_eq!(repairs[1].to_string(), r#""hgt:183 cm" -> "hgt:183cm""#);
+    }
+
+    #[test]
+    fn test_other_documents_keep_values() {
+        let (record, repairs) = normalize("ECL:Blue hgt:6 FT\nhcl:#ABCDEF");
+
+        assert_eq!(record, "ecl:Blue hgt:6FT\nhcl:#ABCDEF");
+        assert_eq!(repairs.len(), 2);
+    }
+
+    #[test]
+    fn test_normalized_passport_is_valid() {
+        use crate::types::Passport;
+
+        let record = "HCL:#623A2F pid:087499704 hgt:74 in ecl:Green\niyr:2012 eyr:2030 byr:1980";
+        assert!(record.parse::<Passport>().is_err());
+
+        let (record, _) = normalize_passport(record);
+        assert!(record.parse::<Passport>().unwrap().is_valid_2());
+    }
+}
@@ -0,0 +1,173 @@
+use crate::records::Record;
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Write},
+    ops::RangeInclusive,
+};
+
+/// Record that could not be parsed, kept aside so the rest of the batch can go on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Quarantined {
+    pub lines: RangeInclusive<usize>,
+    pub text: String,
+    pub error: String,
+}
+
+impl Quarantined {
+    pub fn new(record: Record<String>, error: String) -> Self {
+        Quarantined {
+            lines: record.line..=record.line + record.value.lines().count().max(1) - 1,
+            text: record.value,
+            error,
+        }
+    }
+}
+
+impl fmt::Display for Quarantined {
+    /// Comment with the reason, then the record as it was in the input
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "# lines {}-{}: {}",
+            self.lines.start(),
+            self.lines.end(),
+            self.error
+        )?;
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Parse every record, records that fail to parse go to quarantine instead of stopping the batch
+pub(crate) fn partition<T>(
+    records: impl IntoIterator<Item = Record<String>>,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> (Vec<Record<T>>, Vec<Quarantined>) {
+    let mut parsed = vec![];
+    let mut quarantined = vec![];
+
+    for record in records {
+        match parse(&record.value) {
+            Ok(value) => parsed.push(Record {
+                line: record.line,
+                value,
+            }),
+            Err(error) => quarantined.push(Quarantined::new(record, error)),
+        }
+    }
+
+    (parsed, quarantined)
+}
+
+/// Writes quarantined records one by one
+pub(crate) struct Writer {
+    output: Box<dyn Write + Send>,
+    /// Records written so far, every one after the first goes after a blank line
+    count: usize,
+}
+
+impl Writer {
+    pub fn new(output: impl Write + Send + 'static) -> Self {
+        Writer {
+            output: Box::new(output),
+            count: 0,
+        }
+    }
+
+    /// File is truncated right away, records of an earlier run never stay in it
+    pub fn create(filename: &str) -> io::Result<Self> {
+        Ok(Writer::new(BufWriter::new(File::create(filename)?)))
+    }
+
+    /// Records are separated by blank lines, same as in the input
+    pub fn push(&mut self, quarantined: &Quarantined) -> io::Result<()> {
+        if self.count > 0 {
+            writeln!(self.output)?;
+        }
+        writeln!(self.output, "{}", quarantined)?;
+        self.count += 1;
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// File is written even when nothing is quarantined
+pub(crate) fn write(filename: &str, quarantined: &[Quarantined]) -> io::Result<()> {
+    let mut writer = Writer::create(filename)?;
+    for entry in quarantined {
+        writer.push(entry)?;
+    }
+
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Passport;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_partition() {
+        let records = vec![
+            Record {
+                line: 1,
+                value: "byr:1937 iyr:2017\ncid:147".to_string(),
+            },
+            Record {
+                line: 4,
+                value: "byr:1937 cfg:hhfhff\niyr:2017\nhgt:183cm".to_string(),
+            },
+            Record {
+                line: 8,
+                value: "ecl:gry".to_string(),
+            },
+        ];
+
+        let (parsed, quarantined) = partition(records, str::parse::<Passport>);
+
+        assert_eq!(
+            parsed.iter().map(|record| record.line).collect::<Vec<_>>(),
+            vec![1, 8]
+        );
+        assert_eq!(
+            quarantined,
+            vec![Quarantined {
+                lines: 4..=6,
+                text: "byr:1937 cfg:hhfhff\niyr:2017\nhgt:183cm".into(),
+                error: "Field key is not recognized: cfg".into(),
+            }]
+        );
+        assert_eq!(
+            quarantined[0].to_string(),
+            "# lines 4-6: Field key is not recognized: cfg\nbyr:1937 cfg:hhfhff\niyr:2017\nhgt:183cm"
+        );
+    }
+
+    #[test]
+    fn test_write_replaces_earlier_run() {
+        let filename =
+            std::env::temp_dir().join(format!("day4-quarantine-{}.txt", std::process::id()));
+        let filename = filename.to_str().unwrap();
+        let quarantined = Quarantined {
+            lines: 1..=1,
+            text: "cfg:1".into(),
+            error: "Field key is not recognized: cfg".into(),
+        };
+
+        write(filename, &[quarantined.clone(), quarantined]).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(filename).unwrap(),
+            "# lines 1-1: Field key is not recognized: cfg\ncfg:1\n\n\
+             # lines 1-1: Field key is not recognized: cfg\ncfg:1\n"
+        );
+
+        write(filename, &[]).unwrap();
+        assert_eq!(std::fs::read_to_string(filename).unwrap(), "");
+        std::fs::remove_file(filename).unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+use std::io::{self, BufRead, Lines};
+
+/// Something read from a batch file together with the line it starts on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Record<T> {
+    /// Starts from 1, as in text editors
+    pub line: usize,
+    pub value: T,
+}
+
+impl<T> Record<T> {
+    /// Convert the value, errors point to the line of the record
+    pub fn try_map<U>(self, f: impl FnOnce(T) -> Result<U, String>) -> Result<Record<U>, String> {
+        match f(self.value) {
+            Ok(value) => Ok(Record {
+                line: self.line,
+                value,
+            }),
+            Err(err) => Err(format!("Record at line {} is bad: {}", self.line, err)),
+        }
+    }
+}
+
+/// Reads records separated by blank lines.
+///
+/// Copes with CRLF line endings, a byte order mark, trailing whitespace,
+/// any amount of blank lines between records and at the end of the file.
+pub(crate) struct Records<R> {
+    lines: Lines<R>,
+    line: usize,
+}
+
+impl<R: BufRead> Records<R> {
+    pub fn new(reader: R) -> Self {
+        Records {
+            lines: reader.lines(),
+            line: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = io::Result<Record<String>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record: Option<Record<String>> = None;
+
+        loop {
+            let line = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(err)) => return Some(Err(err)),
+                None => return record.map(Ok),
+            };
+            self.line += 1;
+
+            let line = if self.line == 1 {
+                line.trim_start_matches('\u{feff}')
+            } else {
+                &line
+            }
+            .trim_end();
+
+            match &mut record {
+                _ if line.trim().is_empty() => match record {
+                    Some(_) => return record.map(Ok),
+                    None => continue,
+                },
+                Some(record) => {
+                    record.value.push('\n');
+                    record.value.push_str(line);
+                }
+                None => {
+                    record = Some(Record {
+                        line: self.line,
+                        value: line.to_string(),
+                    })
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn split_records(s: &str) -> Vec<Record<String>> {
+        Records::new(s.as_bytes())
+            .collect::<io::Result<_>>()
+            .unwrap()
+    }
+
+    fn record(line: usize, value: &str) -> Record<String> {
+        Record {
+            line,
+            value: value.into(),
+        }
+    }
+
+    #[test]
+    fn test_split_records() {
+        let cases = &[
+            (
+                "a:1 b:2\nc:3\n\nd:4\n",
+                vec![record(1, "a:1 b:2\nc:3"), record(4, "d:4")],
+            ),
+            (
+                "a:1\r\nb:2\r\n\r\nc:3\r\n",
+                vec![record(1, "a:1\nb:2"), record(4, "c:3")],
+            ),
+            (
+                "\u{feff}a:1\n\nb:2",
+                vec![record(1, "a:1"), record(3, "b:2")],
+            ),
+            (
+                "a:1  \n \t\nb:2\t\n",
+                vec![record(1, "a:1"), record(3, "b:2")],
+            ),
+            (
+                "\n\na:1\n\n\n\nb:2\n\n\n",
+                vec![record(3, "a:1"), record(7, "b:2")],
+            ),
+            ("", vec![]),
+            ("\n \n", vec![]),
+        ];
+
+        for case in cases {
+            assert_eq!(split_records(case.0), case.1, "{:?}", case.0);
+        }
+    }
+
+    #[test]
+    fn test_record_try_map() {
+        assert_eq!(
+            record(3, "12").try_map(|value| value.parse::<u32>().map_err(|err| err.to_string())),
+            Ok(Record { line: 3, value: 12 })
+        );
+        assert_eq!(
+            record(3, "ab").try_map(|value| value.parse::<u32>().map_err(|err| err.to_string())),
+            Err("Record at line 3 is bad: invalid digit found in string".into())
+        );
+    }
+}
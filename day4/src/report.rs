@@ -0,0 +1,296 @@
+use crate::schema::Schema;
+use std::fmt;
+
+/// One thing that is wrong with a field of a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum FieldIssue {
+    Missing {
+        key: String,
+    },
+    Invalid {
+        key: String,
+        reason: String,
+    },
+    Unknown {
+        key: String,
+    },
+    /// Written more than once, when repeated keys are rejected
+    Repeated {
+        key: String,
+    },
+}
+
+impl FieldIssue {
+    pub fn key(&self) -> &str {
+        use FieldIssue::*;
+
+        match self {
+            Missing { key } | Invalid { key, .. } | Unknown { key } | Repeated { key } => key,
+        }
+    }
+}
+
+impl fmt::Display for FieldIssue {
+    /// Example: FieldIssue::Invalid { key: "hcl", reason: "missing '#'" } -> "hcl missing '#'"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use FieldIssue::*;
+
+        match self {
+            Missing { key } => write!(f, "{} missing", key),
+            Invalid { key, reason } => write!(f, "{} {}", key, reason),
+            Unknown { key } => write!(f, "{} is not in schema", key),
+            Repeated { key } => write!(f, "{} is repeated", key),
+        }
+    }
+}
+
+/// Validation result of a single document, no issues -- valid document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Report(pub Vec<FieldIssue>);
+
+impl Report {
+    /// All required fields are present and none is repeated
+    pub fn is_valid_1(&self) -> bool {
+        !self.0.iter().any(|issue| {
+            matches!(
+                issue,
+                FieldIssue::Missing { .. } | FieldIssue::Repeated { .. }
+            )
+        })
+    }
+
+    pub fn is_valid_2(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "valid");
+        }
+
+        let issues: Vec<String> = self.0.iter().map(FieldIssue::to_string).collect();
+        write!(f, "{}", issues.join("; "))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct KeyStats {
+    pub missing: usize,
+    pub invalid: usize,
+    pub unknown: usize,
+    pub repeated: usize,
+}
+
+/// Issue counts of every field across many documents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Summary {
+    pub total: usize,
+    pub valid_1: usize,
+    pub valid_2: usize,
+    /// Documents that break a cross-field rule, when rules are checked.
+    /// Such documents are not valid 2
+    pub breaking_rules: Option<usize>,
+    /// Keys of the schema go first, in the order of definition
+    pub keys: Vec<(String, KeyStats)>,
+}
+
+impl Summary {
+    pub fn new<'a>(schema: &Schema, reports: impl IntoIterator<Item = &'a Report>) -> Self {
+        let mut summary = Summary::empty(schema);
+        for report in reports {
+            summary.add(report);
+        }
+
+        summary
+    }
+
+    /// No documents counted yet
+    pub fn empty(schema: &Schema) -> Self {
+        Summary {
+            total: 0,
+            valid_1: 0,
+            valid_2: 0,
+            breaking_rules: None,
+            keys: schema
+                .keys()
+                .map(|key| (key.to_string(), KeyStats::default()))
+                .collect(),
+        }
+    }
+
+    /// No documents counted yet, cross-field rules are counted as well
+    pub fn with_rules(schema: &Schema) -> Self {
+        Summary {
+            breaking_rules: Some(0),
+            ..Summary::empty(schema)
+        }
+    }
+
+    pub fn add(&mut self, report: &Report) {
+        self.add_checked(report, false);
+    }
+
+    /// Document that breaks a cross-field rule is counted as invalid for rule 2
+    pub fn add_checked(&mut self, report: &Report, breaks_rules: bool) {
+        self.total += 1;
+        self.valid_1 += report.is_valid_1() as usize;
+        self.valid_2 += (report.is_valid_2() && !breaks_rules) as usize;
+        if let Some(breaking_rules) = &mut self.breaking_rules {
+            *breaking_rules += breaks_rules as usize;
+        }
+
+        for issue in &report.0 {
+            let stats = match self.keys.iter().position(|(key, _)| key == issue.key()) {
+                Some(index) => &mut self.keys[index].1,
+                None => {
+                    self.keys
+                        .push((issue.key().to_string(), KeyStats::default()));
+                    &mut self.keys.last_mut().unwrap().1
+                }
+            };
+
+            match issue {
+                FieldIssue::Missing { .. } => stats.missing += 1,
+                FieldIssue::Invalid { .. } => stats.invalid += 1,
+                FieldIssue::Unknown { .. } => stats.unknown += 1,
+                FieldIssue::Repeated { .. } => stats.repeated += 1,
+            }
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<5} {:>8} {:>8} {:>8} {:>8}",
+            "key", "missing", "invalid", "unknown", "repeated"
+        )?;
+        for (key, stats) in &self.keys {
+            writeln!(
+                f,
+                "{:<5} {:>8} {:>8} {:>8} {:>8}",
+                key, stats.missing, stats.invalid, stats.unknown, stats.repeated
+            )?;
+        }
+        writeln!(f, "Total: {}", self.total)?;
+        if let Some(breaking_rules) = self.breaking_rules {
+            writeln!(f, "Breaking rules: {}", breaking_rules)?;
+        }
+        writeln!(f, "Valid 1: {}", self.valid_1)?;
+        write!(f, "Valid 2: {}", self.valid_2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::Document;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use FieldIssue::*;
+
+    fn invalid(key: &str, reason: &str) -> FieldIssue {
+        Invalid {
+            key: key.into(),
+            reason: reason.into(),
+        }
+    }
+
+    #[test]
+    fn test_issue_display() {
+        let cases = &[
+            (Missing { key: "byr".into() }, "byr missing"),
+            (
+                invalid("hgt", "1524cm out of 150..=193"),
+                "hgt 1524cm out of 150..=193",
+            ),
+            (invalid("hcl", "missing '#'"), "hcl missing '#'"),
+            (Unknown { key: "cfg".into() }, "cfg is not in schema"),
+            (Repeated { key: "byr".into() }, "byr is repeated"),
+        ];
+
+        for case in cases {
+            assert_eq!(case.0.to_string(), case.1);
+        }
+    }
+
+    #[test]
+    fn test_passport_schema_issues() {
+        let cases = &[
+            (
+                "eyr:1972 cid:100 hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926",
+                vec![
+                    invalid("eyr", "1972 out of 2020..=2030"),
+                    invalid("hgt", "170 has no unit, expected one of cm, in"),
+                    invalid("pid", "186cm is not 9 digits"),
+                ],
+            ),
+            (
+                "iyr:2019 hcl:602927 eyr:1967 hgt:1524cm ecl:grn pid:012533040",
+                vec![
+                    Missing { key: "byr".into() },
+                    invalid("eyr", "1967 out of 2020..=2030"),
+                    invalid("hgt", "1524cm out of 150..=193"),
+                    invalid("hcl", "missing '#'"),
+                ],
+            ),
+            (
+                "hcl:#dab227 iyr:2012 ecl:zzz eyr:2020 byr:19x7 hgt:cm pid:021572410",
+                vec![
+                    invalid("byr", "19x7 is not a four digit year"),
+                    invalid("hgt", "cm has no number before unit"),
+                    invalid("ecl", "zzz is not one of amb, blu, brn, gry, grn, hzl, oth"),
+                ],
+            ),
+            (
+                "hcl:#zz3d byr:1941 hgt:61in ecl:oth iyr:2010 eyr:2022 pid:260965466 cfg:0",
+                vec![
+                    invalid("hcl", "#zz3d has 'z' that is not a lowercase hex digit"),
+                    Unknown { key: "cfg".into() },
+                ],
+            ),
+            (
+                "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f",
+                vec![],
+            ),
+        ];
+
+        for case in cases {
+            let document: Document = case.0.parse().unwrap();
+            assert_eq!(document.report(Schema::passport()), Report(case.1.clone()));
+        }
+    }
+
+    #[test]
+    fn test_summary() {
+        let schema: Schema = "name required any\nage optional year 1900..=2020"
+            .parse()
+            .unwrap();
+        let reports = vec![
+            Report(vec![]),
+            Report(vec![invalid("age", "3000 out of 1900..=2020")]),
+            Report(vec![
+                Missing { key: "name".into() },
+                Unknown { key: "nick".into() },
+            ]),
+            Report(vec![Repeated { key: "age".into() }]),
+        ];
+
+        let summary = Summary::new(&schema, &reports);
+
+        assert_eq!(
+            summary.to_string(),
+            indoc! {"
+                key    missing  invalid  unknown repeated
+                name         1        0        0        0
+                age          0        1        0        1
+                nick         0        0        1        0
+                Total: 4
+                Valid 1: 2
+                Valid 2: 1"}
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::typed::Year;
+use crate::types::{FieldKind, Passport};
+use std::{fmt, str::FromStr, sync::OnceLock};
+
+const PASSPORT_RULES: &str = include_str!("../schemas/passport.rules");
+
+/// Constraint between two year fields of the same passport
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CrossRule {
+    /// First year is not after the second one, e.g. "iyr <= eyr"
+    NotAfter(FieldKind, FieldKind),
+    /// `to` year is at most `years` after `from` year, e.g. "eyr - iyr <= 10"
+    MaxSpan {
+        to: FieldKind,
+        from: FieldKind,
+        years: u16,
+    },
+}
+
+impl CrossRule {
+    /// Rule holds, or the reason why it does not
+    pub fn check(&self, passport: &Passport) -> Result<(), String> {
+        let year = |kind: FieldKind| {
+            passport
+                .get(kind)
+                .and_then(|field| field.value().parse::<Year>().ok())
+        };
+
+        match *self {
+            CrossRule::NotAfter(first, second) => match (year(first), year(second)) {
+                (Some(first), Some(second)) if first > second => {
+                    Err(format!("{} is after {}", first, second))
+                }
+                _ => Ok(()),
+            },
+            CrossRule::MaxSpan { to, from, years } => match (year(to), year(from)) {
+                (Some(to), Some(from)) if to.0.saturating_sub(from.0) > years => {
+                    Err(format!("{} is {} years after {}", to, to.0 - from.0, from))
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for CrossRule {
+    /// Same text the rule is parsed from
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CrossRule::NotAfter(first, second) => write!(f, "{} <= {}", first, second),
+            CrossRule::MaxSpan { to, from, years } => {
+                write!(f, "{} - {} <= {}", to, from, years)
+            }
+        }
+    }
+}
+
+impl FromStr for CrossRule {
+    type Err = String;
+
+    /// Example: "eyr - iyr <= 10" -> CrossRule::MaxSpan { to: ExpirationYear, from: IssueYear, years: 10 }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Rules only compare years
+        let year = |key: &str| match FieldKind::from_key(key)? {
+            kind @ (FieldKind::BirthYear | FieldKind::IssueYear | FieldKind::ExpirationYear) => {
+                Ok(kind)
+            }
+            _ => Err(format!("Field {} is not a year", key)),
+        };
+
+        match s.split_whitespace().collect::<Vec<&str>>()[..] {
+            [first, "<=", second] => Ok(CrossRule::NotAfter(year(first)?, year(second)?)),
+            [to, "-", from, "<=", years] => Ok(CrossRule::MaxSpan {
+                to: year(to)?,
+                from: year(from)?,
+                years: years
+                    .parse()
+                    .map_err(|_| format!("Number of years is bad: {}", years))?,
+            }),
+            _ => Err(format!("Rule is not recognized: {}", s)),
+        }
+    }
+}
+
+/// Cross-field rule that a passport breaks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RuleFailure {
+    pub rule: CrossRule,
+    pub reason: String,
+}
+
+impl fmt::Display for RuleFailure {
+    /// Example: "iyr <= eyr broken: 2020 is after 2019"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} broken: {}", self.rule, self.reason)
+    }
+}
+
+/// Set of cross-field rules that are checked on the whole passport
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Rules(pub Vec<CrossRule>);
+
+impl Rules {
+    /// Rules bundled with the program
+    pub fn passport() -> &'static Rules {
+        static RULES: OnceLock<Rules> = OnceLock::new();
+
+        RULES.get_or_init(|| {
+            PASSPORT_RULES
+                .parse()
+                .expect("Bundled passport rules are bad")
+        })
+    }
+
+    /// Every rule that `passport` breaks, in the order rules are defined
+    pub fn evaluate(&self, passport: &Passport) -> Vec<RuleFailure> {
+        self.0
+            .iter()
+            .filter_map(|rule| {
+                rule.check(passport).err().map(|reason| RuleFailure {
+                    rule: *rule,
+                    reason,
+                })
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Rules {
+    type Err = String;
+
+    /// One rule per line, blank lines and `#` comments are skipped
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.lines()
+            .enumerate()
+            .filter(|(_, line)| !(line.trim().is_empty() || line.trim_start().starts_with('#')))
+            .map(|(index, line)| {
+                line.parse()
+                    .map_err(|err| format!("Rules line {} is bad: {}", index + 1, err))
+            })
+            .collect::<Result<Vec<CrossRule>, String>>()
+            .map(Rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!(
+            Rules::passport().0,
+            vec![
+                CrossRule::NotAfter(FieldKind::IssueYear, FieldKind::ExpirationYear),
+                CrossRule::NotAfter(FieldKind::BirthYear, FieldKind::IssueYear),
+                CrossRule::MaxSpan {
+                    to: FieldKind::ExpirationYear,
+                    from: FieldKind::IssueYear,
+                    years: 10,
+                },
+            ]
+        );
+
+        for rule in &Rules::passport().0 {
+            assert_eq!(rule.to_string().parse::<CrossRule>(), Ok(*rule));
+        }
+
+        assert_eq!(
+            "iyr <= eyr\nbyr >= iyr".parse::<Rules>(),
+            Err("Rules line 2 is bad: Rule is not recognized: byr >= iyr".into())
+        );
+        assert_eq!(
+            "eyr - xyz <= 10".parse::<Rules>(),
+            Err("Rules line 1 is bad: Field key is not recognized: xyz".into())
+        );
+        assert_eq!(
+            "hgt <= eyr".parse::<Rules>(),
+            Err("Rules line 1 is bad: Field hgt is not a year".into())
+        );
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let cases = &[
+            ("byr:1937 iyr:2017 eyr:2020", vec![]),
+            (
+                "byr:1937 iyr:2021 eyr:2020",
+                vec!["iyr <= eyr broken: 2021 is after 2020"],
+            ),
+            (
+                "byr:2012 iyr:2011 eyr:2030",
+                vec![
+                    "byr <= iyr broken: 2012 is after 2011",
+                    "eyr - iyr <= 10 broken: 2030 is 19 years after 2011",
+                ],
+            ),
+            // Missing and malformed years are left to single field validation
+            ("byr:1937 eyr:2020", vec![]),
+            ("byr:1937 iyr:20 eyr:2020", vec![]),
+        ];
+
+        for case in cases {
+            let passport = case.0.parse::<Passport>().unwrap();
+            let failures: Vec<String> = Rules::passport()
+                .evaluate(&passport)
+                .iter()
+                .map(RuleFailure::to_string)
+                .collect();
+
+            assert_eq!(failures, case.1, "{}", case.0);
+        }
+    }
+}
@@ -0,0 +1,516 @@
+use crate::report::{FieldIssue, Report};
+use crate::types::{dedup, DuplicatePolicy};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::read_to_string,
+    ops::RangeInclusive,
+    str::FromStr,
+    sync::OnceLock,
+};
+
+const PASSPORT_SCHEMA: &str = include_str!("../schemas/passport.schema");
+
+/// How the value of a single field is validated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Rule {
+    /// Four digit year, e.g. "1937"
+    Year(RangeInclusive<u32>),
+    /// Number followed by a unit, every unit has its own range, e.g. "183cm"
+    Measure(Vec<(String, RangeInclusive<u32>)>),
+    /// `#` followed by six lowercase hex digits, e.g. "#fffffd"
+    HexColor,
+    /// One value from the fixed set, e.g. "gry"
+    OneOf(Vec<String>),
+    /// Fixed amount of decimal digits, e.g. "012345678"
+    Digits(usize),
+    Any,
+}
+
+impl Rule {
+    pub fn check(&self, value: &str) -> bool {
+        self.validate(value).is_ok()
+    }
+
+    /// Same as `check`, but explains what is wrong with the value
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        use Rule::*;
+
+        let is_number =
+            |value: &str| !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit());
+        let in_range = |number: u32, range: &RangeInclusive<u32>| {
+            if range.contains(&number) {
+                Ok(())
+            } else {
+                Err(format!("{} out of {:?}", value, range))
+            }
+        };
+
+        match self {
+            Year(range) => {
+                if value.len() == 4 && is_number(value) {
+                    in_range(value.parse().unwrap_or_default(), range)
+                } else {
+                    Err(format!("{} is not a four digit year", value))
+                }
+            }
+            Measure(units) => {
+                match units
+                    .iter()
+                    .find_map(|(unit, range)| Some((value.strip_suffix(unit.as_str())?, range)))
+                {
+                    Some((number, range)) if is_number(number) => {
+                        in_range(number.parse().unwrap_or(u32::MAX), range)
+                    }
+                    Some(_) => Err(format!("{} has no number before unit", value)),
+                    None => Err(format!(
+                        "{} has no unit, expected one of {}",
+                        value,
+                        units
+                            .iter()
+                            .map(|(unit, _)| unit.as_str())
+                            .collect::<Vec<&str>>()
+                            .join(", ")
+                    )),
+                }
+            }
+            HexColor => match value.strip_prefix('#') {
+                None => Err("missing '#'".into()),
+                Some(digits) => match digits
+                    .chars()
+                    .find(|chr| !matches!(chr, 'a'..='f' | '0'..='9'))
+                {
+                    Some(chr) => Err(format!(
+                        "{} has '{}' that is not a lowercase hex digit",
+                        value, chr
+                    )),
+                    None if digits.len() != 6 => {
+                        Err(format!("{} must have 6 hex digits after '#'", value))
+                    }
+                    None => Ok(()),
+                },
+            },
+            OneOf(values) => {
+                if values.iter().any(|allowed| allowed == value) {
+                    Ok(())
+                } else {
+                    Err(format!("{} is not one of {}", value, values.join(", ")))
+                }
+            }
+            Digits(length) => {
+                if value.len() == *length && is_number(value) {
+                    Ok(())
+                } else {
+                    Err(format!("{} is not {} digits", value, length))
+                }
+            }
+            Any => Ok(()),
+        }
+    }
+}
+
+fn parse_range(s: &str) -> Result<RangeInclusive<u32>, String> {
+    match s.split("..=").collect::<Vec<&str>>()[..] {
+        [start, end] => match (start.parse(), end.parse()) {
+            (Ok(start), Ok(end)) => Ok(start..=end),
+            _ => Err(format!("Range bounds are not numbers: {}", s)),
+        },
+        _ => Err(format!("Can not parse range {}", s)),
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    /// Example: "year 1920..=2002" -> Rule::Year(1920..=2002)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Rule::*;
+
+        let mut words = s.split_whitespace();
+        let name = words.next().ok_or("Rule is empty")?;
+        let args: Vec<&str> = words.collect();
+
+        match (name, &args[..]) {
+            ("year", [range]) => Ok(Year(parse_range(range)?)),
+            ("measure", units) if !units.is_empty() && units.len() % 2 == 0 => units
+                .chunks(2)
+                .map(|unit| Ok((unit[0].to_string(), parse_range(unit[1])?)))
+                .collect::<Result<_, String>>()
+                .map(Measure),
+            ("hex-color", []) => Ok(HexColor),
+            ("one-of", values) if !values.is_empty() => {
+                Ok(OneOf(values.iter().map(|&value| value.into()).collect()))
+            }
+            ("digits", [length]) => length
+                .parse()
+                .map(Digits)
+                .map_err(|_| format!("Digits length is not a number: {}", length)),
+            ("any", []) => Ok(Any),
+            _ => Err(format!("Rule is not recognized: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FieldSpec {
+    pub key: String,
+    pub required: bool,
+    pub rule: Rule,
+}
+
+impl FromStr for FieldSpec {
+    type Err = String;
+
+    /// Example: "cid optional any" -> FieldSpec { key: "cid", required: false, rule: Rule::Any }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_whitespace().collect::<Vec<&str>>()[..] {
+            [key, presence, ref rule @ ..] if !rule.is_empty() => Ok(FieldSpec {
+                key: key.into(),
+                required: match presence {
+                    "required" => true,
+                    "optional" => false,
+                    case => return Err(format!("Field presence is not recognized: {}", case)),
+                },
+                rule: rule.join(" ").parse()?,
+            }),
+            _ => Err(format!("Can not parse field definition {}", s)),
+        }
+    }
+}
+
+/// Set of fields that make up one document type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Schema(Vec<FieldSpec>);
+
+impl Schema {
+    /// Rules of the North Pole passport, shipped with the program
+    pub fn passport() -> &'static Schema {
+        static SCHEMA: OnceLock<Schema> = OnceLock::new();
+
+        SCHEMA.get_or_init(|| {
+            PASSPORT_SCHEMA
+                .parse()
+                .expect("Bundled passport schema is bad")
+        })
+    }
+
+    pub fn from_file(filename: &str) -> Result<Self, String> {
+        read_to_string(filename)
+            .map_err(|err| format!("Can not read schema {}: {}", filename, err))?
+            .parse()
+    }
+
+    pub fn spec(&self, key: &str) -> Option<&FieldSpec> {
+        self.0.iter().find(|spec| spec.key == key)
+    }
+
+    /// Unknown keys are never valid
+    pub fn is_value_valid(&self, key: &str, value: &str) -> bool {
+        self.spec(key).is_some_and(|spec| spec.rule.check(value))
+    }
+
+    /// Find every missing, invalid and unknown field among `key:value` pairs
+    pub fn validate<'a>(
+        &self,
+        fields: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Vec<FieldIssue> {
+        let fields: Vec<(&str, &str)> = fields.into_iter().collect();
+        let mut issues = vec![];
+
+        for spec in &self.0 {
+            let mut values = fields
+                .iter()
+                .filter(|(key, _)| *key == spec.key)
+                .map(|(_, value)| value)
+                .peekable();
+
+            if spec.required && values.peek().is_none() {
+                issues.push(FieldIssue::Missing {
+                    key: spec.key.clone(),
+                });
+            }
+
+            issues.extend(values.filter_map(|value| {
+                spec.rule
+                    .validate(value)
+                    .err()
+                    .map(|reason| FieldIssue::Invalid {
+                        key: spec.key.clone(),
+                        reason,
+                    })
+            }));
+        }
+
+        issues.extend(
+            fields
+                .iter()
+                .filter(|(key, _)| self.spec(key).is_none())
+                .map(|(key, _)| FieldIssue::Unknown {
+                    key: key.to_string(),
+                }),
+        );
+
+        issues
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|spec| spec.key.as_str())
+    }
+
+    /// All required keys are present
+    pub fn has_required_fields<'a>(&self, keys: impl IntoIterator<Item = &'a str>) -> bool {
+        let keys: Vec<&str> = keys.into_iter().collect();
+
+        self.0
+            .iter()
+            .filter(|spec| spec.required)
+            .all(|spec| keys.contains(&spec.key.as_str()))
+    }
+}
+
+impl FromStr for Schema {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = s
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !(line.trim().is_empty() || line.trim_start().starts_with('#')))
+            .map(|(index, line)| {
+                line.parse()
+                    .map_err(|err| format!("Schema line {} is bad: {}", index + 1, err))
+            })
+            .collect::<Result<Vec<FieldSpec>, String>>()?;
+
+        match fields
+            .iter()
+            .enumerate()
+            .find(|(index, spec)| fields[..*index].iter().any(|seen| seen.key == spec.key))
+        {
+            Some((_, spec)) => Err(format!("Field {} is defined twice", spec.key)),
+            None => Ok(Schema(fields)),
+        }
+    }
+}
+
+/// Any document made of `key:value` entries, keys are not known in advance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Document {
+    entries: BTreeMap<String, String>,
+    /// Keys written more than once, rejected by `DuplicatePolicy::Reject`
+    repeated: BTreeSet<String>,
+}
+
+impl Document {
+    pub fn report(&self, schema: &Schema) -> Report {
+        let mut issues = schema.validate(
+            self.entries
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str())),
+        );
+        issues.extend(
+            self.repeated
+                .iter()
+                .map(|key| FieldIssue::Repeated { key: key.clone() }),
+        );
+
+        Report(issues)
+    }
+
+    pub fn is_valid_1(&self, schema: &Schema) -> bool {
+        self.repeated.is_empty()
+            && schema.has_required_fields(self.entries.keys().map(String::as_str))
+    }
+
+    pub fn is_valid_2(&self, schema: &Schema) -> bool {
+        self.is_valid_1(schema)
+            && self
+                .entries
+                .iter()
+                .all(|(key, value)| schema.is_value_valid(key, value))
+    }
+
+    /// Parse document, resolving repeated keys with `policy`
+    pub fn parse_with(s: &str, policy: DuplicatePolicy) -> Result<Self, String> {
+        let entries = s
+            .split_whitespace()
+            .map(|entry| match entry.split(':').collect::<Vec<&str>>()[..] {
+                [key, value] => Ok((key.to_string(), value.to_string())),
+                _ => Err(format!("Can not parse field entry {}", entry)),
+            })
+            .collect::<Result<Vec<(String, String)>, String>>()?;
+
+        let deduped = dedup(entries, policy);
+
+        Ok(Document {
+            entries: deduped.entries,
+            repeated: deduped.repeated,
+        })
+    }
+}
+
+impl FromStr for Document {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Document::parse_with(s, DuplicatePolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+    use Rule::*;
+
+    #[test]
+    fn test_rule_from_str() {
+        let cases = &[
+            ("year 1920..=2002", Ok(Year(1920..=2002))),
+            (
+                "measure cm 150..=193 in 59..=76",
+                Ok(Measure(vec![
+                    ("cm".into(), 150..=193),
+                    ("in".into(), 59..=76),
+                ])),
+            ),
+            ("hex-color", Ok(HexColor)),
+            (
+                "one-of amb blu",
+                Ok(OneOf(vec!["amb".into(), "blu".into()])),
+            ),
+            ("digits 9", Ok(Digits(9))),
+            ("any", Ok(Any)),
+            ("year 1920", Err("Can not parse range 1920".into())),
+            (
+                "year a..=b",
+                Err("Range bounds are not numbers: a..=b".into()),
+            ),
+            (
+                "measure cm",
+                Err("Rule is not recognized: measure cm".into()),
+            ),
+            (
+                "digits nine",
+                Err("Digits length is not a number: nine".into()),
+            ),
+            ("regex .*", Err("Rule is not recognized: regex .*".into())),
+        ];
+
+        for case in cases {
+            assert_eq!(case.0.parse::<Rule>(), case.1);
+        }
+    }
+
+    #[test]
+    fn test_rule_check() {
+        let height = Measure(vec![("cm".into(), 150..=193), ("in".into(), 59..=76)]);
+        let cases = &[
+            (Year(1920..=2002), "1937", true),
+            (Year(1920..=2002), "+937", false),
+            (Year(1920..=2002), "19370", false),
+            (height.clone(), "152cm", true),
+            (height.clone(), "62in", true),
+            (height.clone(), "1524cm", false),
+            (height.clone(), "cm", false),
+            (height.clone(), "+152cm", false),
+            (height, "1é", false),
+            (HexColor, "#fac9b0", true),
+            (HexColor, "#FAC9B0", false),
+            (OneOf(vec!["amb".into()]), "amb", true),
+            (OneOf(vec!["amb".into()]), "am", false),
+            (Digits(3), "012", true),
+            (Digits(3), "+12", false),
+            (Any, "", true),
+        ];
+
+        for (index, case) in cases.iter().enumerate() {
+            assert_eq!(
+                case.0.check(case.1),
+                case.2,
+                "failed rule check with case {:?}\n----- at index {}",
+                case,
+                index
+            );
+        }
+    }
+
+    #[test]
+    fn test_schema_from_str() {
+        let schema: Schema = indoc! {"
+            # Library card
+            name required any
+
+            age  optional year 1900..=2020
+        "}
+        .parse()
+        .unwrap();
+
+        assert_eq!(
+            schema,
+            Schema(vec![
+                FieldSpec {
+                    key: "name".into(),
+                    required: true,
+                    rule: Any,
+                },
+                FieldSpec {
+                    key: "age".into(),
+                    required: false,
+                    rule: Year(1900..=2020),
+                },
+            ])
+        );
+
+        assert_eq!(
+            "name maybe any".parse::<Schema>(),
+            Err("Schema line 1 is bad: Field presence is not recognized: maybe".into())
+        );
+        assert_eq!(
+            "name required any\nname optional any".parse::<Schema>(),
+            Err("Field name is defined twice".into())
+        );
+    }
+
+    #[test]
+    fn test_passport_schema_is_bundled() {
+        let schema = Schema::passport();
+
+        assert_eq!(schema.0.len(), 8);
+        assert_eq!(schema.spec("byr").unwrap().rule, Year(1920..=2002));
+        assert_eq!(schema.spec("cid").unwrap().required, false);
+    }
+
+    #[test]
+    fn test_document_validation() {
+        let schema: Schema = "name required any\nage optional year 1900..=2020"
+            .parse()
+            .unwrap();
+
+        let cases = &[
+            ("name:lain age:1998", true, true),
+            ("name:lain", true, true),
+            ("name:lain age:3000", true, false),
+            ("name:lain nick:meandmymind", true, false),
+            ("age:1998", false, false),
+        ];
+
+        for case in cases {
+            let document: Document = case.0.parse().unwrap();
+            assert_eq!(document.is_valid_1(&schema), case.1, "{}", case.0);
+            assert_eq!(document.is_valid_2(&schema), case.2, "{}", case.0);
+        }
+
+        assert_eq!(
+            "name:lain:iwakura".parse::<Document>(),
+            Err("Can not parse field entry name:lain:iwakura".into())
+        );
+
+        let document: Document = "name:lain name:alice".parse().unwrap();
+        assert!(!document.is_valid_1(&schema));
+        assert_eq!(
+            document.report(&schema),
+            Report(vec![FieldIssue::Repeated { key: "name".into() }])
+        );
+    }
+}
@@ -0,0 +1,409 @@
+use crate::normalize::{self, NormalizeMode};
+use crate::quarantine::{self, Quarantined};
+use crate::records::Record;
+use crate::report::{Report, Summary};
+use crate::rules::{RuleFailure, Rules};
+use crate::schema::Schema;
+use crate::types::{DuplicatePolicy, Passport};
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Mutex,
+    },
+    thread,
+};
+
+/// Records in flight per worker, between the reader and the output
+const WINDOW_PER_JOB: usize = 16;
+
+/// Apply `map` to every item on `jobs` threads, `sink` gets the results in the order of `items`
+/// and returns `false` to stop, no more items are taken then.
+///
+/// Items are pulled from `items` on the current thread and only when the window has room,
+/// so at most `jobs * WINDOW_PER_JOB` items are held in memory at once.
+/// With less than two jobs everything is done on the current thread.
+pub(crate) fn ordered_map<T: Send, U: Send>(
+    items: impl IntoIterator<Item = T>,
+    jobs: usize,
+    map: impl Fn(T) -> U + Sync,
+    mut sink: impl FnMut(U) -> bool + Send,
+) {
+    if jobs < 2 {
+        for item in items {
+            if !sink(map(item)) {
+                break;
+            }
+        }
+        return;
+    }
+
+    let window = jobs * WINDOW_PER_JOB;
+    let (task_sender, task_receiver) = mpsc::sync_channel::<(usize, T)>(window);
+    let (done_sender, done_receiver) = mpsc::sync_channel::<(usize, U)>(window);
+    // Reader takes a permit for every item, output gives it back when the item is written
+    let (permit_sender, permit_receiver) = mpsc::sync_channel::<()>(window);
+    for _ in 0..window {
+        permit_sender.send(()).expect("Permit channel has room");
+    }
+
+    let task_receiver = Mutex::new(task_receiver);
+    // Set when the sink does not want more results
+    let stop = AtomicBool::new(false);
+    let (map, task_receiver, stop) = (&map, &task_receiver, &stop);
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let done_sender = done_sender.clone();
+            scope.spawn(move || loop {
+                let task = task_receiver.lock().expect("Worker panicked").recv();
+                match task {
+                    Ok(_) if stop.load(Ordering::Relaxed) => break,
+                    Ok((index, item)) => {
+                        if done_sender.send((index, map(item))).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            });
+        }
+        drop(done_sender);
+
+        scope.spawn(move || {
+            // Results that came before the ones in front of them
+            let mut pending = BTreeMap::new();
+            let mut next = 0;
+
+            for (index, value) in done_receiver {
+                pending.insert(index, value);
+                while let Some(value) = pending.remove(&next) {
+                    if !sink(value) {
+                        stop.store(true, Ordering::Relaxed);
+                        // Workers and the reader see the closed channels and stop
+                        return;
+                    }
+                    next += 1;
+                    // Reader may be gone already
+                    let _ = permit_sender.send(());
+                }
+            }
+        });
+
+        for (index, item) in items.into_iter().enumerate() {
+            if permit_receiver.recv().is_err() || stop.load(Ordering::Relaxed) {
+                break;
+            }
+            if task_sender.send((index, item)).is_err() {
+                break;
+            }
+        }
+        drop(task_sender);
+    });
+}
+
+/// How every passport record is checked
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Checker {
+    pub duplicates: DuplicatePolicy,
+    pub normalize: Option<NormalizeMode>,
+    /// Put bad records to quarantine instead of failing the whole batch
+    pub lenient: bool,
+    /// Print duplicates, issues and broken rules of every record
+    pub report: bool,
+    /// Check cross-field rules, a passport that breaks one is not valid 2
+    pub rules: bool,
+}
+
+enum Outcome {
+    Parsed {
+        report: Report,
+        failures: Vec<RuleFailure>,
+    },
+    Quarantined(Quarantined),
+    Failed(String),
+}
+
+/// Everything that is known about a single record after the check
+struct Checked {
+    /// Lines to print for the record
+    messages: Vec<String>,
+    outcome: Outcome,
+}
+
+impl Checker {
+    fn check(&self, record: Record<String>) -> Checked {
+        let (record, mut messages) = match self.normalize {
+            Some(mode) => mode.normalize_record(record, normalize::normalize_passport),
+            None => (record, vec![]),
+        };
+
+        let (passport, duplicates) = match Passport::parse_with(&record.value, self.duplicates) {
+            Ok(parsed) => parsed,
+            Err(error) if self.lenient => {
+                return Checked {
+                    messages,
+                    outcome: Outcome::Quarantined(Quarantined::new(record, error)),
+                }
+            }
+            Err(error) => {
+                return Checked {
+                    messages,
+                    outcome: Outcome::Failed(format!(
+                        "Record at line {} is bad: {}",
+                        record.line, error
+                    )),
+                }
+            }
+        };
+
+        let report = passport.report();
+        let failures = if self.rules {
+            Rules::passport().evaluate(&passport)
+        } else {
+            vec![]
+        };
+
+        if self.report {
+            let line = record.line;
+            messages.extend(
+                duplicates
+                    .iter()
+                    .map(|duplicate| format!("Line {}: {}", line, duplicate)),
+            );
+            if !report.is_valid_2() {
+                messages.push(format!("Line {}: {}", line, report));
+            }
+            messages.extend(
+                failures
+                    .iter()
+                    .map(|failure| format!("Line {}: {}", line, failure)),
+            );
+        }
+
+        Checked {
+            messages,
+            outcome: Outcome::Parsed { report, failures },
+        }
+    }
+}
+
+/// Counts over all records of the stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Tally {
+    pub summary: Summary,
+    /// How many times every passport rule was broken, in the order of rules
+    pub broken_rules: Vec<usize>,
+    pub quarantined: usize,
+}
+
+impl Tally {
+    fn new(rules: bool) -> Self {
+        Tally {
+            summary: if rules {
+                Summary::with_rules(Schema::passport())
+            } else {
+                Summary::empty(Schema::passport())
+            },
+            broken_rules: vec![0; Rules::passport().0.len()],
+            quarantined: 0,
+        }
+    }
+
+    fn add(&mut self, report: &Report, failures: &[RuleFailure]) {
+        self.summary.add_checked(report, !failures.is_empty());
+
+        let rules = &Rules::passport().0;
+        for failure in failures {
+            if let Some(index) = rules.iter().position(|rule| *rule == failure.rule) {
+                self.broken_rules[index] += 1;
+            }
+        }
+    }
+}
+
+/// Check passport records on `jobs` threads, messages of every record are written to `output`
+/// in the order of the records.
+///
+/// `source` names the input in error messages, stream stops at the first read error
+/// and at the first bad record unless the checker is lenient.
+pub(crate) fn validate(
+    records: impl IntoIterator<Item = io::Result<Record<String>>>,
+    source: &str,
+    checker: &Checker,
+    jobs: usize,
+    quarantine: &mut quarantine::Writer,
+    output: &mut (impl Write + Send),
+) -> Result<Tally, String> {
+    let mut tally = Tally::new(checker.rules);
+    let mut error = None;
+
+    // Nothing is read after a read error
+    let records = records.into_iter().scan(false, |failed, record| {
+        if *failed {
+            return None;
+        }
+        *failed = record.is_err();
+        Some(record)
+    });
+
+    ordered_map(
+        records,
+        jobs,
+        |record| match record {
+            Ok(record) => checker.check(record),
+            Err(err) => Checked {
+                messages: vec![],
+                outcome: Outcome::Failed(format!("Can not read {}: {}", source, err)),
+            },
+        },
+        |checked| {
+            let result = checked
+                .messages
+                .iter()
+                .try_for_each(|message| writeln!(output, "{}", message))
+                .map_err(|err| format!("Can not write output: {}", err))
+                .and_then(|_| match checked.outcome {
+                    Outcome::Parsed { report, failures } => {
+                        tally.add(&report, &failures);
+                        Ok(())
+                    }
+                    Outcome::Quarantined(quarantined) => {
+                        tally.quarantined += 1;
+                        quarantine
+                            .push(&quarantined)
+                            .map_err(|err| format!("Can not write quarantine: {}", err))
+                    }
+                    Outcome::Failed(err) => Err(err),
+                });
+
+            if let Err(err) = result {
+                error = Some(err);
+            }
+            error.is_none()
+        },
+    );
+
+    match error {
+        Some(err) => Err(err),
+        None => Ok(tally),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::records::Records;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_ordered_map() {
+        let mut results = vec![];
+        ordered_map(
+            0..1000u64,
+            4,
+            |n| n * n,
+            |n| {
+                results.push(n);
+                true
+            },
+        );
+
+        assert_eq!(results, (0..1000u64).map(|n| n * n).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_ordered_map_stops() {
+        for jobs in &[1, 4] {
+            let mut pulled = 0;
+            let mut results = vec![];
+            ordered_map(
+                (0..100_000u64).inspect(|_| pulled += 1),
+                *jobs,
+                |n| n,
+                |n| {
+                    results.push(n);
+                    n < 5
+                },
+            );
+
+            assert_eq!(results, vec![0, 1, 2, 3, 4, 5], "{} jobs", jobs);
+            // Only the window can be ahead of the sink
+            assert!(
+                pulled <= 6 + jobs * WINDOW_PER_JOB,
+                "{} jobs pulled {}",
+                jobs,
+                pulled
+            );
+        }
+    }
+
+    fn validate_text(
+        text: &str,
+        checker: &Checker,
+        jobs: usize,
+    ) -> (Result<Tally, String>, String) {
+        let mut output = vec![];
+        let result = validate(
+            Records::new(text.as_bytes()),
+            "test",
+            checker,
+            jobs,
+            &mut quarantine::Writer::new(io::sink()),
+            &mut output,
+        );
+
+        (result, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_parallel_output_is_sequential_output() {
+        let text = [
+            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd\nbyr:1937 iyr:2017 cid:147 hgt:183cm",
+            "iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884\nhcl:#cfa07d byr:1929",
+            "hcl:#ae17e1 iyr:2013\neyr:2024\necl:brn pid:760753108 byr:1931\nhgt:179cm",
+            "hcl:#cfa07d eyr:2025 pid:166559648\niyr:2011 ecl:brn hgt:59in",
+            "byr:1937 byr:1937 iyr:2021 eyr:2020 HGT:183 CM",
+        ]
+        .repeat(50)
+        .join("\n\n");
+
+        let checker = Checker {
+            normalize: Some(NormalizeMode::Apply),
+            duplicates: DuplicatePolicy::KeepLast,
+            report: true,
+            rules: true,
+            ..Checker::default()
+        };
+        let (sequential, sequential_output) = validate_text(&text, &checker, 1);
+        let sequential = sequential.unwrap();
+
+        assert_eq!(sequential.summary.total, 250);
+        assert_eq!(sequential.summary.valid_1, 100);
+        assert_eq!(sequential.summary.valid_2, 50);
+        assert_eq!(sequential.summary.breaking_rules, Some(150));
+        assert_eq!(sequential.broken_rules, vec![50, 0, 100]);
+
+        for jobs in &[2, 3, 8] {
+            let (parallel, parallel_output) = validate_text(&text, &checker, *jobs);
+
+            assert_eq!(parallel, Ok(sequential.clone()), "{} jobs", jobs);
+            assert_eq!(parallel_output, sequential_output, "{} jobs", jobs);
+        }
+    }
+
+    #[test]
+    fn test_stream_stops_at_bad_record() {
+        let text = "byr:1937\n\nbyr:1937 cfg:1\n\nbyr:1937";
+
+        for jobs in &[1, 4] {
+            let (result, _) = validate_text(text, &Checker::default(), *jobs);
+
+            assert_eq!(
+                result,
+                Err("Record at line 3 is bad: Field key is not recognized: cfg".into())
+            );
+        }
+    }
+}
@@ -0,0 +1,471 @@
+use crate::schema::Schema;
+use crate::types::{Field, FieldKind, Passport};
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+/// Four digit year, any year when parsed, ranges of the fields are in the passport schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Year(pub u16);
+
+impl FromStr for Year {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() == 4 && s.bytes().all(|b| b.is_ascii_digit()) {
+            Ok(Year(s.parse().unwrap_or_default()))
+        } else {
+            Err(format!("{} is not a four digit year", s))
+        }
+    }
+}
+
+impl fmt::Display for Year {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HeightUnit {
+    Centimeters,
+    Inches,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Height {
+    pub value: u32,
+    pub unit: HeightUnit,
+}
+
+impl FromStr for Height {
+    type Err = String;
+
+    /// Example: "183cm" -> Height { value: 183, unit: HeightUnit::Centimeters },
+    /// ranges of the units are in the passport schema
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, unit) = match (s.strip_suffix("cm"), s.strip_suffix("in")) {
+            (Some(value), _) => (value, HeightUnit::Centimeters),
+            (_, Some(value)) => (value, HeightUnit::Inches),
+            _ => return Err(format!("{} has no unit, expected one of cm, in", s)),
+        };
+
+        if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("{} has no number before unit", s));
+        }
+
+        value
+            .parse()
+            .map(|value| Height { value, unit })
+            .map_err(|_| format!("{} is too large", s))
+    }
+}
+
+impl fmt::Display for Height {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let unit = match self.unit {
+            HeightUnit::Centimeters => "cm",
+            HeightUnit::Inches => "in",
+        };
+        write!(f, "{}{}", self.value, unit)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Rgb {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl FromStr for Rgb {
+    type Err = String;
+
+    /// Example: "#ff8000" -> Rgb { red: 255, green: 128, blue: 0 }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix('#').ok_or("missing '#'")?;
+
+        if digits.len() != 6
+            || !digits
+                .bytes()
+                .all(|b| matches!(b, b'a'..=b'f' | b'0'..=b'9'))
+        {
+            return Err(format!("{} is not six lowercase hex digits", s));
+        }
+
+        let channel = |index: usize| u8::from_str_radix(&digits[index..index + 2], 16).unwrap();
+
+        Ok(Rgb {
+            red: channel(0),
+            green: channel(2),
+            blue: channel(4),
+        })
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EyeColor {
+    Amber,
+    Blue,
+    Brown,
+    Gray,
+    Green,
+    Hazel,
+    Other,
+}
+
+impl FromStr for EyeColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use EyeColor::*;
+
+        match s {
+            "amb" => Ok(Amber),
+            "blu" => Ok(Blue),
+            "brn" => Ok(Brown),
+            "gry" => Ok(Gray),
+            "grn" => Ok(Green),
+            "hzl" => Ok(Hazel),
+            "oth" => Ok(Other),
+            _ => Err(format!("{} is not an eye color", s)),
+        }
+    }
+}
+
+impl fmt::Display for EyeColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use EyeColor::*;
+
+        let code = match self {
+            Amber => "amb",
+            Blue => "blu",
+            Brown => "brn",
+            Gray => "gry",
+            Green => "grn",
+            Hazel => "hzl",
+            Other => "oth",
+        };
+        write!(f, "{}", code)
+    }
+}
+
+/// Nine digits, leading zeros included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PassportId(pub u32);
+
+impl FromStr for PassportId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() == 9 && s.bytes().all(|b| b.is_ascii_digit()) {
+            Ok(PassportId(s.parse().unwrap_or_default()))
+        } else {
+            Err(format!("{} is not 9 digits", s))
+        }
+    }
+}
+
+impl fmt::Display for PassportId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:09}", self.0)
+    }
+}
+
+/// Passport that passed all the rules of the passport schema, see `Schema::passport`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ValidPassport {
+    pub birth_year: Year,
+    pub issue_year: Year,
+    pub expiration_year: Year,
+    pub height: Height,
+    pub hair_color: Rgb,
+    pub eye_color: EyeColor,
+    pub passport_id: PassportId,
+    pub country_id: Option<String>,
+}
+
+/// Value of a required field that passed its schema rule, every problem with the field goes to `issues`
+fn required<T: FromStr<Err = String>>(
+    passport: &Passport,
+    kind: FieldKind,
+    issues: &mut Vec<String>,
+) -> Option<T> {
+    let rule = &Schema::passport()
+        .spec(kind.key())
+        .expect("Passport schema has every field")
+        .rule;
+
+    match passport.get(kind) {
+        Some(field) => rule
+            .validate(field.value())
+            .and_then(|_| field.value().parse())
+            .map_err(|reason| issues.push(format!("{} {}", kind, reason)))
+            .ok(),
+        None => {
+            issues.push(format!("{} missing", kind));
+            None
+        }
+    }
+}
+
+impl TryFrom<&Passport> for ValidPassport {
+    type Error = String;
+
+    /// Error lists every issue of the passport, e.g. "byr missing; hgt 170 out of 150..=193"
+    fn try_from(passport: &Passport) -> Result<Self, Self::Error> {
+        let mut issues = vec![];
+        let issues = &mut issues;
+
+        let birth_year = required(passport, FieldKind::BirthYear, issues);
+        let issue_year = required(passport, FieldKind::IssueYear, issues);
+        let expiration_year = required(passport, FieldKind::ExpirationYear, issues);
+        let height = required(passport, FieldKind::Height, issues);
+        let hair_color = required(passport, FieldKind::HairColor, issues);
+        let eye_color = required(passport, FieldKind::EyeColor, issues);
+        let passport_id = required(passport, FieldKind::PassportId, issues);
+        issues.extend(
+            passport
+                .repeated()
+                .map(|kind| format!("{} is repeated", kind)),
+        );
+
+        match (
+            birth_year,
+            issue_year,
+            expiration_year,
+            height,
+            hair_color,
+            eye_color,
+            passport_id,
+        ) {
+            (
+                Some(birth_year),
+                Some(issue_year),
+                Some(expiration_year),
+                Some(height),
+                Some(hair_color),
+                Some(eye_color),
+                Some(passport_id),
+            ) if issues.is_empty() => Ok(ValidPassport {
+                birth_year,
+                issue_year,
+                expiration_year,
+                height,
+                hair_color,
+                eye_color,
+                passport_id,
+                country_id: passport
+                    .get(FieldKind::CountryId)
+                    .map(|field| field.value().to_string()),
+            }),
+            _ => Err(issues.join("; ")),
+        }
+    }
+}
+
+impl FromStr for ValidPassport {
+    type Err = String;
+
+    /// Example: "ecl:gry pid:860033327 eyr:2020 ..." -> ValidPassport { eye_color: EyeColor::Gray, ... }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ValidPassport::try_from(&s.parse::<Passport>()?)
+    }
+}
+
+impl From<&ValidPassport> for Passport {
+    fn from(passport: &ValidPassport) -> Self {
+        vec![
+            Field::BirthYear(passport.birth_year.to_string()),
+            Field::IssueYear(passport.issue_year.to_string()),
+            Field::ExpirationYear(passport.expiration_year.to_string()),
+            Field::Height(passport.height.to_string()),
+            Field::HairColor(passport.hair_color.to_string()),
+            Field::EyeColor(passport.eye_color.to_string()),
+            Field::PassportId(passport.passport_id.to_string()),
+        ]
+        .into_iter()
+        .chain(passport.country_id.clone().map(Field::CountryId))
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_values_from_str() {
+        assert_eq!("1937".parse(), Ok(Year(1937)));
+        assert_eq!(
+            "937".parse::<Year>(),
+            Err("937 is not a four digit year".into())
+        );
+
+        assert_eq!(
+            "183cm".parse(),
+            Ok(Height {
+                value: 183,
+                unit: HeightUnit::Centimeters
+            })
+        );
+        assert_eq!(
+            "62in".parse(),
+            Ok(Height {
+                value: 62,
+                unit: HeightUnit::Inches
+            })
+        );
+        assert_eq!(
+            "1524cm".parse(),
+            Ok(Height {
+                value: 1524,
+                unit: HeightUnit::Centimeters
+            })
+        );
+        assert_eq!(
+            "99999999999in".parse::<Height>(),
+            Err("99999999999in is too large".into())
+        );
+        assert_eq!(
+            "62".parse::<Height>(),
+            Err("62 has no unit, expected one of cm, in".into())
+        );
+        assert_eq!(
+            "écm".parse::<Height>(),
+            Err("écm has no number before unit".into())
+        );
+
+        assert_eq!(
+            "#ff8000".parse(),
+            Ok(Rgb {
+                red: 255,
+                green: 128,
+                blue: 0
+            })
+        );
+        assert_eq!("ff8000".parse::<Rgb>(), Err("missing '#'".into()));
+        assert_eq!(
+            "#ff80é".parse::<Rgb>(),
+            Err("#ff80é is not six lowercase hex digits".into())
+        );
+
+        assert_eq!("hzl".parse(), Ok(EyeColor::Hazel));
+        assert_eq!(
+            "red".parse::<EyeColor>(),
+            Err("red is not an eye color".into())
+        );
+
+        assert_eq!("000000001".parse(), Ok(PassportId(1)));
+        assert_eq!(
+            "0000000001".parse::<PassportId>(),
+            Err("0000000001 is not 9 digits".into())
+        );
+    }
+
+    #[test]
+    fn test_values_display() {
+        let height = Height {
+            value: 62,
+            unit: HeightUnit::Inches,
+        };
+        let color = Rgb {
+            red: 10,
+            green: 128,
+            blue: 255,
+        };
+
+        assert_eq!(Year(2002).to_string(), "2002");
+        assert_eq!(height.to_string(), "62in");
+        assert_eq!(color.to_string(), "#0a80ff");
+        assert_eq!(EyeColor::Other.to_string(), "oth");
+        assert_eq!(PassportId(1).to_string(), "000000001");
+    }
+
+    #[test]
+    fn test_valid_passport_from_str() {
+        let passport: ValidPassport =
+            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f"
+                .parse()
+                .unwrap();
+
+        assert_eq!(
+            passport,
+            ValidPassport {
+                birth_year: Year(1980),
+                issue_year: Year(2012),
+                expiration_year: Year(2030),
+                height: Height {
+                    value: 74,
+                    unit: HeightUnit::Inches
+                },
+                hair_color: Rgb {
+                    red: 0x62,
+                    green: 0x3a,
+                    blue: 0x2f
+                },
+                eye_color: EyeColor::Green,
+                passport_id: PassportId(87499704),
+                country_id: None,
+            }
+        );
+
+        assert_eq!(
+            "eyr:1972 cid:100 hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926"
+                .parse::<ValidPassport>(),
+            Err("eyr 1972 out of 2020..=2030; hgt 170 has no unit, expected one of cm, in; pid 186cm is not 9 digits".into())
+        );
+        assert_eq!(
+            "pid:087499704 ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f byr:1980"
+                .parse::<ValidPassport>(),
+            Err("hgt missing; byr is repeated".into())
+        );
+    }
+
+    #[test]
+    fn test_valid_passport_matches_rule_2() {
+        let cases = &[
+            (
+                "eyr:1972 cid:100 hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926",
+                false,
+            ),
+            (
+                "iyr:2019 hcl:602927 eyr:1967 hgt:1524cm ecl:grn pid:012533040",
+                false,
+            ),
+            (
+                "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f",
+                true,
+            ),
+            (
+                "eyr:2029 ecl:blu cid:129 byr:1989 iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm",
+                true,
+            ),
+            (
+                "hcl:#888785 hgt:164cm byr:2001 iyr:2015 cid:88 pid:545766238 ecl:hzl eyr:2022",
+                true,
+            ),
+            (
+                "pid:087499704 hgt:194cm ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f",
+                false,
+            ),
+        ];
+
+        for (case, valid) in cases {
+            let passport: Passport = case.parse().unwrap();
+            let typed = ValidPassport::try_from(&passport);
+
+            assert_eq!(typed.is_ok(), *valid, "{}", case);
+            // Typed passports follow the same schema as the reports
+            assert_eq!(passport.report().is_valid_2(), *valid, "{}", case);
+            if let Ok(typed) = typed {
+                assert_eq!(Passport::from(&typed), passport);
+            }
+        }
+    }
+}
@@ -1,7 +1,63 @@
-use std::{collections::HashSet, str::FromStr};
-use strum::EnumCount;
+use crate::report::{FieldIssue, Report};
+use crate::schema::Schema;
+use crate::typed::ValidPassport;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    fmt,
+    iter::FromIterator,
+    str::FromStr,
+};
+use strum::{Display, EnumIter, EnumString, IntoStaticStr};
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, EnumCount)]
+/// Kind of a passport field, serialized as the key it is written with
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Display,
+    EnumIter,
+    EnumString,
+    IntoStaticStr,
+)]
+pub(crate) enum FieldKind {
+    #[strum(serialize = "byr")]
+    BirthYear,
+    #[strum(serialize = "iyr")]
+    IssueYear,
+    #[strum(serialize = "eyr")]
+    ExpirationYear,
+    #[strum(serialize = "hgt")]
+    Height,
+    #[strum(serialize = "hcl")]
+    HairColor,
+    #[strum(serialize = "ecl")]
+    EyeColor,
+    #[strum(serialize = "pid")]
+    PassportId,
+    #[strum(serialize = "cid")]
+    CountryId,
+}
+
+impl FieldKind {
+    /// Example: FieldKind::EyeColor -> "ecl"
+    pub fn key(self) -> &'static str {
+        self.into()
+    }
+
+    /// Example: "ecl" -> FieldKind::EyeColor
+    pub fn from_key(key: &str) -> Result<Self, String> {
+        key.parse()
+            .map_err(|_| format!("Field key is not recognized: {}", key))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum Field {
     BirthYear(String),
     IssueYear(String),
@@ -14,111 +70,261 @@ pub(crate) enum Field {
 }
 
 impl Field {
-    fn is_value_in_valid_range(&self) -> bool {
+    pub fn new(kind: FieldKind, value: String) -> Self {
+        match kind {
+            FieldKind::BirthYear => Field::BirthYear(value),
+            FieldKind::IssueYear => Field::IssueYear(value),
+            FieldKind::ExpirationYear => Field::ExpirationYear(value),
+            FieldKind::Height => Field::Height(value),
+            FieldKind::HairColor => Field::HairColor(value),
+            FieldKind::EyeColor => Field::EyeColor(value),
+            FieldKind::PassportId => Field::PassportId(value),
+            FieldKind::CountryId => Field::CountryId(value),
+        }
+    }
+
+    pub fn kind(&self) -> FieldKind {
         use Field::*;
 
-        // Suppose we deal with only ASCII strings for easier logic...
         match self {
-            BirthYear(value) => {
-                value.len() == 4 && {
-                    let year = value.parse::<u32>().unwrap_or_default();
-                    (1920..=2002).contains(&year)
-                }
-            }
-            IssueYear(value) => {
-                value.len() == 4 && {
-                    let year = value.parse::<u32>().unwrap_or_default();
-                    (2010..=2020).contains(&year)
-                }
-            }
-            ExpirationYear(value) => {
-                value.len() == 4 && {
-                    let year = value.parse::<u32>().unwrap_or_default();
-                    (2020..=2030).contains(&year)
-                }
-            }
-            Height(value) => {
-                // Guard against incorrect values
-                value.len() > 2 && {
-                    let height: u32 = value[..value.len() - 2].parse().unwrap_or_default();
-
-                    match &value[value.len() - 2..] {
-                        "cm" => (150..=193).contains(&height),
-                        "in" => (59..=76).contains(&height),
-                        _ => false,
-                    }
-                }
-            }
-            HairColor(value) => {
-                value.len() == 7
-                    && matches!(value.chars().next(), Some('#'))
-                    && value
-                        .chars()
-                        .skip(1)
-                        .all(|chr| matches!(chr, 'a'..='f' | '0'..='9'))
-            }
-            EyeColor(value) => {
-                matches!(
-                    value.as_str(),
-                    "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth"
-                )
-            }
-            PassportId(value) => value.len() == 9 && value.parse::<u32>().is_ok(),
-            CountryId(_) => true,
+            BirthYear(_) => FieldKind::BirthYear,
+            IssueYear(_) => FieldKind::IssueYear,
+            ExpirationYear(_) => FieldKind::ExpirationYear,
+            Height(_) => FieldKind::Height,
+            HairColor(_) => FieldKind::HairColor,
+            EyeColor(_) => FieldKind::EyeColor,
+            PassportId(_) => FieldKind::PassportId,
+            CountryId(_) => FieldKind::CountryId,
+        }
+    }
+
+    /// Key of the field as it is written in a passport
+    pub fn key(&self) -> &'static str {
+        self.kind().key()
+    }
+
+    pub fn value(&self) -> &str {
+        use Field::*;
+
+        match self {
+            BirthYear(value)
+            | IssueYear(value)
+            | ExpirationYear(value)
+            | Height(value)
+            | HairColor(value)
+            | EyeColor(value)
+            | PassportId(value)
+            | CountryId(value) => value,
         }
     }
 }
 
+impl fmt::Display for Field {
+    /// Example: Field::EyeColor("gry") -> "ecl:gry"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.key(), self.value())
+    }
+}
+
 impl FromStr for Field {
     type Err = String;
 
     /// Example: "ecl:gry" -> Field::EyeColor("gry")
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use Field::*;
-
         match s.split(':').collect::<Vec<&str>>()[..] {
-            [a, b] => match a {
-                "byr" => Ok(BirthYear(b.into())),
-                "iyr" => Ok(IssueYear(b.into())),
-                "eyr" => Ok(ExpirationYear(b.into())),
-                "hgt" => Ok(Height(b.into())),
-                "hcl" => Ok(HairColor(b.into())),
-                "ecl" => Ok(EyeColor(b.into())),
-                "pid" => Ok(PassportId(b.into())),
-                "cid" => Ok(CountryId(b.into())),
-                case => Err(format!("Field key is not recognized: {}", case)),
-            },
+            [key, value] => Ok(Field::new(FieldKind::from_key(key)?, value.into())),
             _ => Err(format!("Can not parse field entry {}", s)),
         }
     }
 }
 
+/// What to do when a passport has the same key more than once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum DuplicatePolicy {
+    /// Passport with a repeated key is invalid, the first entry is kept for the report
+    #[default]
+    Reject,
+    KeepFirst,
+    KeepLast,
+}
+
+impl FromStr for DuplicatePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reject" => Ok(DuplicatePolicy::Reject),
+            "keep-first" => Ok(DuplicatePolicy::KeepFirst),
+            "keep-last" => Ok(DuplicatePolicy::KeepLast),
+            case => Err(format!("Duplicate policy is not recognized: {}", case)),
+        }
+    }
+}
+
+/// Repeated key that was resolved by `DuplicatePolicy`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Duplicate<T> {
+    pub kept: T,
+    pub dropped: T,
+}
+
+impl<T: PartialEq> Duplicate<T> {
+    /// Same key with a different value, not just the same entry written twice
+    pub fn is_conflict(&self) -> bool {
+        self.kept != self.dropped
+    }
+}
+
+impl<T: PartialEq + fmt::Display> fmt::Display for Duplicate<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_conflict() {
+            write!(f, "conflict: kept {}, dropped {}", self.kept, self.dropped)
+        } else {
+            write!(f, "{} is written twice", self.kept)
+        }
+    }
+}
+
+/// Entries by their key and what happened to repeated keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Deduped<K, V> {
+    pub entries: BTreeMap<K, V>,
+    /// Entries that were dropped on the way
+    pub duplicates: Vec<Duplicate<V>>,
+    /// Keys that were repeated when the policy rejects them
+    pub repeated: BTreeSet<K>,
+}
+
+/// Put `(key, value)` entries into a map, resolving repeated keys with `policy`
+pub(crate) fn dedup<K: Ord, V: Clone>(
+    entries: impl IntoIterator<Item = (K, V)>,
+    policy: DuplicatePolicy,
+) -> Deduped<K, V> {
+    let mut map = BTreeMap::new();
+    let mut duplicates = vec![];
+    let mut repeated = BTreeSet::new();
+
+    for (key, value) in entries {
+        match map.get_mut(&key) {
+            None => {
+                map.insert(key, value);
+            }
+            Some(seen) => match policy {
+                DuplicatePolicy::Reject => {
+                    repeated.insert(key);
+                }
+                DuplicatePolicy::KeepFirst => duplicates.push(Duplicate {
+                    kept: seen.clone(),
+                    dropped: value,
+                }),
+                DuplicatePolicy::KeepLast => duplicates.push(Duplicate {
+                    kept: value.clone(),
+                    dropped: std::mem::replace(seen, value),
+                }),
+            },
+        }
+    }
+
+    Deduped {
+        entries: map,
+        duplicates,
+        repeated,
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) struct Passport(HashSet<Field>);
+pub(crate) struct Passport {
+    fields: BTreeMap<FieldKind, Field>,
+    /// Kinds written more than once, rejected by `DuplicatePolicy::Reject`
+    repeated: BTreeSet<FieldKind>,
+}
 
 impl Passport {
-    const REQUIRED_FIELDS_NUM: usize = Field::COUNT - 1;
-    fn is_field_required(field: &Field) -> bool {
-        !matches!(field, Field::CountryId(_))
+    pub fn fields(&self) -> impl Iterator<Item = &Field> {
+        self.fields.values()
+    }
+
+    pub fn get(&self, kind: FieldKind) -> Option<&Field> {
+        self.fields.get(&kind)
     }
 
     pub fn is_valid_1(&self) -> bool {
-        // All but optinal fields are present -- valid passport
-        self.0
-            .iter()
-            .filter(|&field| Passport::is_field_required(field))
-            .count()
-            == Passport::REQUIRED_FIELDS_NUM
+        // Passport with a rejected repeated key is never valid
+        self.repeated.is_empty()
+            // All but optinal fields are present -- valid passport
+            && Schema::passport().has_required_fields(self.fields().map(Field::key))
     }
 
+    /// Every missing, invalid and repeated field, checked against the passport schema
+    pub fn report(&self) -> Report {
+        let mut issues =
+            Schema::passport().validate(self.fields().map(|field| (field.key(), field.value())));
+        issues.extend(self.repeated.iter().map(|kind| FieldIssue::Repeated {
+            key: kind.to_string(),
+        }));
+
+        Report(issues)
+    }
+
+    /// Every field is in its range, same as the passport converts to `ValidPassport`
     pub fn is_valid_2(&self) -> bool {
-        // All but optinal fields are present
-        self.is_valid_1()
-            // All field values are in their ranges
-            && self
-                .0
-                .iter()
-                .all(|field| field.is_value_in_valid_range())
+        ValidPassport::try_from(self).is_ok()
+    }
+
+    /// Kinds that were written more than once and rejected
+    pub fn repeated(&self) -> impl Iterator<Item = FieldKind> + '_ {
+        self.repeated.iter().copied()
+    }
+
+    /// Same passport with `repeated` kinds rejected, as if they were written more than once
+    pub fn with_repeated(mut self, repeated: impl IntoIterator<Item = FieldKind>) -> Self {
+        self.repeated.extend(repeated);
+        self
+    }
+
+    /// Parse passport, resolving repeated keys with `policy`
+    pub fn parse_with(
+        s: &str,
+        policy: DuplicatePolicy,
+    ) -> Result<(Self, Vec<Duplicate<Field>>), String> {
+        let fields = s
+            .split_whitespace()
+            .map(|field_entry| field_entry.parse())
+            .collect::<Result<Vec<Field>, String>>()?;
+
+        let deduped = dedup(
+            fields.into_iter().map(|field| (field.kind(), field)),
+            policy,
+        );
+
+        Ok((
+            Passport {
+                fields: deduped.entries,
+                repeated: deduped.repeated,
+            },
+            deduped.duplicates,
+        ))
+    }
+}
+
+/// Later fields overwrite earlier fields of the same kind
+impl FromIterator<Field> for Passport {
+    fn from_iter<I: IntoIterator<Item = Field>>(fields: I) -> Self {
+        Passport {
+            fields: fields
+                .into_iter()
+                .map(|field| (field.kind(), field))
+                .collect(),
+            repeated: BTreeSet::new(),
+        }
+    }
+}
+
+impl fmt::Display for Passport {
+    /// Fields in kind order on a single line, e.g. "byr:1937 ecl:gry"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields: Vec<String> = self.fields().map(Field::to_string).collect();
+        write!(f, "{}", fields.join(" "))
     }
 }
 
@@ -126,16 +332,7 @@ impl FromStr for Passport {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s
-            .split_whitespace()
-            .map(|field_entry| field_entry.parse())
-            .collect::<Result<Vec<Field>, String>>()
-        {
-            Ok(fields) => Ok(Passport {
-                0: fields.into_iter().collect(),
-            }),
-            Err(err) => Err(err),
-        }
+        Passport::parse_with(s, DuplicatePolicy::default()).map(|(passport, _)| passport)
     }
 }
 
@@ -201,7 +398,7 @@ mod tests {
 
         for (index, case) in cases.iter().enumerate() {
             assert_eq!(
-                case.0.is_value_in_valid_range(),
+                Schema::passport().is_value_valid(case.0.key(), case.0.value()),
                 case.1,
                 "failed field_value_in_range with case {:?}\n----- at index {}",
                 case,
@@ -215,53 +412,45 @@ mod tests {
         let cases = &[
             (
                 "byr:123 ecl:gry",
-                Ok(Passport {
-                    0: vec![BirthYear("123".into()), EyeColor("gry".into())]
-                        .into_iter()
-                        .collect(),
-                }),
+                Ok(vec![BirthYear("123".into()), EyeColor("gry".into())]
+                    .into_iter()
+                    .collect::<Passport>()),
             ),
             (
                 "byr:123 ecl:gry",
-                Ok(Passport {
-                    0: vec![BirthYear("123".into()), EyeColor("gry".into())]
-                        .into_iter()
-                        .collect(),
-                }),
+                Ok(vec![BirthYear("123".into()), EyeColor("gry".into())]
+                    .into_iter()
+                    .collect::<Passport>()),
             ),
             (
                 "byr:123 ecl:gry",
-                Ok(Passport {
-                    0: vec![BirthYear("123".into()), EyeColor("gry".into())]
-                        .into_iter()
-                        .collect(),
-                }),
+                Ok(vec![BirthYear("123".into()), EyeColor("gry".into())]
+                    .into_iter()
+                    .collect::<Passport>()),
             ),
             (
                 "byr:123",
-                Ok(Passport {
-                    0: vec![BirthYear("123".into())].into_iter().collect(),
-                }),
+                Ok(vec![BirthYear("123".into())]
+                    .into_iter()
+                    .collect::<Passport>()),
             ),
             (
                 indoc! {"
                     ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
                     byr:1937 iyr:2017 cid:147 hgt:183cm
                 "},
-                Ok(Passport {
-                    0: vec![
-                        EyeColor("gry".into()),
-                        PassportId("860033327".into()),
-                        ExpirationYear("2020".into()),
-                        HairColor("#fffffd".into()),
-                        BirthYear("1937".into()),
-                        IssueYear("2017".into()),
-                        CountryId("147".into()),
-                        Height("183cm".into()),
-                    ]
-                    .into_iter()
-                    .collect(),
-                }),
+                Ok(vec![
+                    EyeColor("gry".into()),
+                    PassportId("860033327".into()),
+                    ExpirationYear("2020".into()),
+                    HairColor("#fffffd".into()),
+                    BirthYear("1937".into()),
+                    IssueYear("2017".into()),
+                    CountryId("147".into()),
+                    Height("183cm".into()),
+                ]
+                .into_iter()
+                .collect::<Passport>()),
             ),
             (
                 indoc! {"
@@ -270,19 +459,17 @@ mod tests {
                     ecl:brn pid:760753108 byr:1931
                     hgt:179cm
                 "},
-                Ok(Passport {
-                    0: vec![
-                        EyeColor("brn".into()),
-                        PassportId("760753108".into()),
-                        ExpirationYear("2024".into()),
-                        HairColor("#ae17e1".into()),
-                        BirthYear("1931".into()),
-                        IssueYear("2013".into()),
-                        Height("179cm".into()),
-                    ]
-                    .into_iter()
-                    .collect(),
-                }),
+                Ok(vec![
+                    EyeColor("brn".into()),
+                    PassportId("760753108".into()),
+                    ExpirationYear("2024".into()),
+                    HairColor("#ae17e1".into()),
+                    BirthYear("1931".into()),
+                    IssueYear("2013".into()),
+                    Height("179cm".into()),
+                ]
+                .into_iter()
+                .collect::<Passport>()),
             ),
             (
                 indoc! {"
@@ -302,44 +489,38 @@ mod tests {
     fn test_is_passport_valid_1() {
         let cases = &[
             (
-                Passport {
-                    0: vec![
-                        EyeColor("brn".into()),
-                        PassportId("760753108".into()),
-                        ExpirationYear("2024".into()),
-                        HairColor("#ae17e1".into()),
-                        BirthYear("1931".into()),
-                        IssueYear("2013".into()),
-                        Height("179cm".into()),
-                    ]
-                    .into_iter()
-                    .collect(),
-                },
+                vec![
+                    EyeColor("brn".into()),
+                    PassportId("760753108".into()),
+                    ExpirationYear("2024".into()),
+                    HairColor("#ae17e1".into()),
+                    BirthYear("1931".into()),
+                    IssueYear("2013".into()),
+                    Height("179cm".into()),
+                ]
+                .into_iter()
+                .collect::<Passport>(),
                 true,
             ),
             (
-                Passport {
-                    0: vec![
-                        EyeColor("brn".into()),
-                        PassportId("760753108".into()),
-                        ExpirationYear("2024".into()),
-                        HairColor("#ae17e1".into()),
-                        BirthYear("1931".into()),
-                        IssueYear("2013".into()),
-                        CountryId("147".into()),
-                        Height("179cm".into()),
-                    ]
-                    .into_iter()
-                    .collect(),
-                },
+                vec![
+                    EyeColor("brn".into()),
+                    PassportId("760753108".into()),
+                    ExpirationYear("2024".into()),
+                    HairColor("#ae17e1".into()),
+                    BirthYear("1931".into()),
+                    IssueYear("2013".into()),
+                    CountryId("147".into()),
+                    Height("179cm".into()),
+                ]
+                .into_iter()
+                .collect::<Passport>(),
                 true,
             ),
             (
-                Passport {
-                    0: vec![BirthYear("123".into()), EyeColor("gry".into())]
-                        .into_iter()
-                        .collect(),
-                },
+                vec![BirthYear("123".into()), EyeColor("gry".into())]
+                    .into_iter()
+                    .collect::<Passport>(),
                 false,
             ),
         ];
@@ -354,4 +535,181 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    fn test_passport_report() {
+        let cases = &[
+            "eyr:1972 cid:100 hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926",
+            "iyr:2019 hcl:602927 eyr:1967 hgt:1524cm ecl:grn pid:012533040",
+            "hcl:dab227 iyr:2012 ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277",
+            "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980 hcl:#623a2f",
+            "eyr:2029 ecl:blu cid:129 byr:1989 iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm",
+            "byr:1931",
+        ];
+
+        for case in cases {
+            let passport: Passport = case.parse().unwrap();
+            let report = passport.report();
+
+            assert_eq!(report.is_valid_1(), passport.is_valid_1(), "{}", case);
+            assert_eq!(report.is_valid_2(), passport.is_valid_2(), "{}", case);
+        }
+    }
+
+    #[test]
+    fn test_passport_duplicates() {
+        let entry = "byr:1990 ecl:gry byr:1991 ecl:gry";
+
+        let (passport, duplicates) = Passport::parse_with(entry, DuplicatePolicy::Reject).unwrap();
+        assert_eq!(passport.to_string(), "byr:1990 ecl:gry");
+        assert!(duplicates.is_empty());
+        assert_eq!(
+            passport.report().to_string(),
+            "iyr missing; eyr missing; hgt missing; hcl missing; pid missing; \
+             byr is repeated; ecl is repeated"
+        );
+
+        let (passport, duplicates) =
+            Passport::parse_with(entry, DuplicatePolicy::KeepFirst).unwrap();
+        assert_eq!(
+            passport,
+            vec![BirthYear("1990".into()), EyeColor("gry".into())]
+                .into_iter()
+                .collect::<Passport>()
+        );
+        assert_eq!(
+            duplicates
+                .iter()
+                .map(Duplicate::to_string)
+                .collect::<Vec<String>>(),
+            vec![
+                "conflict: kept byr:1990, dropped byr:1991",
+                "ecl:gry is written twice",
+            ]
+        );
+
+        let (passport, duplicates) =
+            Passport::parse_with(entry, DuplicatePolicy::KeepLast).unwrap();
+        assert_eq!(
+            passport,
+            vec![BirthYear("1991".into()), EyeColor("gry".into())]
+                .into_iter()
+                .collect::<Passport>()
+        );
+        assert_eq!(
+            duplicates[0],
+            Duplicate {
+                kept: BirthYear("1991".into()),
+                dropped: BirthYear("1990".into()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejected_passport_is_invalid() {
+        let entry =
+            "byr:1990 iyr:2015 eyr:2025 hgt:180cm hcl:#123abc ecl:grn pid:012345678 byr:1990";
+        let passport: Passport = entry.parse().unwrap();
+
+        assert!(!passport.is_valid_1());
+        assert!(!passport.is_valid_2());
+        assert!(Passport::parse_with(entry, DuplicatePolicy::KeepFirst)
+            .unwrap()
+            .0
+            .is_valid_2());
+    }
+
+    #[test]
+    fn test_duplicates_do_not_count_as_required_fields() {
+        let entry = "byr:1990 byr:1991 iyr:2015 eyr:2025 hgt:180cm hcl:#123abc ecl:grn cid:1";
+        let (passport, _) = Passport::parse_with(entry, DuplicatePolicy::KeepLast).unwrap();
+
+        assert!(!passport.is_valid_1());
+    }
+}
+
+/// Generators of passports for property tests
+#[cfg(test)]
+pub(crate) mod strategies {
+    use super::*;
+    use proptest::prelude::*;
+    use strum::IntoEnumIterator;
+    use Field::*;
+
+    /// Any value that survives a trip through the passport text: no whitespace and no ':'
+    pub fn field() -> impl Strategy<Value = Field> {
+        let kinds: Vec<FieldKind> = FieldKind::iter().collect();
+        (prop::sample::select(kinds), "[^:\\s]*").prop_map(|(kind, value)| Field::new(kind, value))
+    }
+
+    /// Mostly values that look like the real ones, so some passports are valid
+    pub fn plausible_field() -> impl Strategy<Value = Field> {
+        prop_oneof![
+            "19[0-9]{2}|20[0-2][0-9]".prop_map(BirthYear),
+            "20[0-3][0-9]".prop_map(IssueYear),
+            "20[0-3][0-9]".prop_map(ExpirationYear),
+            "1[4-9][0-9]cm|[5-7][0-9]in".prop_map(Height),
+            "#[0-9a-f]{6}".prop_map(HairColor),
+            "amb|blu|brn|gry|grn|hzl|oth|xyz".prop_map(EyeColor),
+            "[0-9]{8,10}".prop_map(PassportId),
+            "[0-9]{1,3}".prop_map(CountryId),
+            field(),
+        ]
+    }
+
+    pub fn passport() -> impl Strategy<Value = Passport> {
+        prop::collection::vec(prop_oneof![field(), plausible_field()], 0..12)
+            .prop_map(|fields| fields.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn field_from_any_str_does_not_panic(s in "\\PC*") {
+            let _ = s.parse::<Field>();
+        }
+
+        #[test]
+        fn value_check_does_not_panic(field in strategies::field()) {
+            let _ = Schema::passport().is_value_valid(field.key(), field.value());
+        }
+
+        #[test]
+        fn multibyte_values_do_not_panic(value in "[0-9]{0,3}[éü€😀]{1,2}[0-9]{0,2}(cm|in)?") {
+            for kind in &[FieldKind::BirthYear, FieldKind::Height, FieldKind::HairColor, FieldKind::PassportId] {
+                let field = Field::new(*kind, value.clone());
+                prop_assert!(!Schema::passport().is_value_valid(field.key(), field.value()));
+            }
+        }
+
+        #[test]
+        fn passport_from_any_str_does_not_panic(s in "\\PC*") {
+            if let Ok(passport) = s.parse::<Passport>() {
+                let _ = passport.is_valid_2();
+                let _ = passport.report();
+            }
+        }
+
+        #[test]
+        fn field_round_trip(field in strategies::field()) {
+            prop_assert_eq!(field.to_string().parse::<Field>(), Ok(field));
+        }
+
+        #[test]
+        fn passport_round_trip(passport in strategies::passport()) {
+            prop_assert_eq!(passport.to_string().parse::<Passport>(), Ok(passport));
+        }
+
+        #[test]
+        fn passport_is_valid_as_its_report(passport in strategies::passport()) {
+            let report = passport.report();
+            prop_assert_eq!(passport.is_valid_1(), report.is_valid_1());
+            prop_assert_eq!(passport.is_valid_2(), report.is_valid_2());
+        }
+    }
 }
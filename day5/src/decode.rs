@@ -0,0 +1,185 @@
+use crate::types::{Layout, Seat};
+
+/// What a letter of a boarding pass means for the bit decoder
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Letter {
+    Bad,
+    Row(usize),
+    Column(usize),
+}
+
+impl Layout {
+    fn letter(&self, letter: char) -> Letter {
+        match letter {
+            _ if letter == self.rows.lower => Letter::Row(0),
+            _ if letter == self.rows.upper => Letter::Row(1),
+            _ if letter == self.columns.lower => Letter::Column(0),
+            _ if letter == self.columns.upper => Letter::Column(1),
+            _ => Letter::Bad,
+        }
+    }
+
+    /// Read the boarding pass as two binary numbers at once, upper letters are ones.
+    ///
+    /// Gives the same seats and errors as `parse_seat`, without narrowing ranges letter by letter.
+    pub fn decode(&self, s: &str) -> Result<Seat, String> {
+        if let Some(issue) = self.check_letters(s) {
+            return Err(format!("Boarding pass {} {}", s, issue));
+        }
+
+        let (mut row, mut column) = (0, 0);
+        for letter in s.chars() {
+            match self.letter(letter) {
+                Letter::Row(bit) => row = row << 1 | bit,
+                Letter::Column(bit) => column = column << 1 | bit,
+                Letter::Bad => unreachable!("Letters are checked"),
+            }
+        }
+
+        Ok(Seat::new(row, column))
+    }
+
+    /// Decode one boarding pass per line, blank lines are skipped.
+    ///
+    /// Layouts with ASCII letters go through a lookup table over the raw bytes,
+    /// passes that fail there are decoded again with `decode` to explain the error.
+    pub fn decode_all(&self, text: &str) -> Result<Vec<Seat>, String> {
+        let lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        let explain = |line: usize, pass: &str| {
+            let err = self.decode(pass).err().unwrap_or_default();
+            format!("Seat {} at line {} is bad: {}", pass, line, err)
+        };
+
+        let letters = [
+            self.rows.lower,
+            self.rows.upper,
+            self.columns.lower,
+            self.columns.upper,
+        ];
+        if !letters.iter().all(char::is_ascii) {
+            return lines
+                .map(|(line, pass)| self.decode(pass).map_err(|_| explain(line, pass)))
+                .collect();
+        }
+
+        let mut table = [Letter::Bad; 256];
+        for letter in &letters {
+            table[*letter as usize] = self.letter(*letter);
+        }
+
+        let mut seats = Vec::with_capacity(text.len() / (self.code_length() + 1));
+        for (line, pass) in lines {
+            let bytes = pass.as_bytes();
+            if bytes.len() != self.code_length() {
+                return Err(explain(line, pass));
+            }
+
+            let (row_bytes, column_bytes) = bytes.split_at(self.rows.code_length());
+            let (mut row, mut column) = (0, 0);
+            for byte in row_bytes {
+                match table[*byte as usize] {
+                    Letter::Row(bit) => row = row << 1 | bit,
+                    _ => return Err(explain(line, pass)),
+                }
+            }
+            for byte in column_bytes {
+                match table[*byte as usize] {
+                    Letter::Column(bit) => column = column << 1 | bit,
+                    _ => return Err(explain(line, pass)),
+                }
+            }
+
+            seats.push(Seat::new(row, column));
+        }
+
+        Ok(seats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use proptest::prelude::*;
+    use test_case::test_case;
+
+    #[test_case("BFFFBBFRRR", Ok(Seat::new(70, 7)))]
+    #[test_case("BBFFBBFRLL", Ok(Seat::new(102, 4)))]
+    #[test_case("RFFFBBFRRB", Err("Boarding pass RFFFBBFRRB has column letter R at index 0 in the row part".to_string()) ; "letters of both dimensions mixed")]
+    #[test_case("BFFFBBFRRX", Err("Boarding pass BFFFBBFRRX has bad letter X at index 9".to_string()))]
+    #[test_case("BFFFBBFRR", Err("Boarding pass BFFFBBFRR has 9 letters, expected 10".to_string()))]
+    #[test_case("BFFFBBFFRR", Err("Boarding pass BFFFBBFFRR has row letter F at index 7 in the column part".to_string()))]
+    fn decode(x: &str, seat: Result<Seat, String>) {
+        assert_eq!(Layout::default().decode(x), seat)
+    }
+
+    #[test]
+    fn decode_all() {
+        let layout = Layout::default();
+
+        assert_eq!(
+            layout.decode_all("BFFFBBFRRR\r\n\nFFFBBBFRRR\n"),
+            Ok(vec![Seat::new(70, 7), Seat::new(14, 7)])
+        );
+        assert_eq!(
+            layout.decode_all("BFFFBBFRRR\nFFFBBBFRRL\nFFFBBBLRRR\n"),
+            Err(
+                "Seat FFFBBBLRRR at line 3 is bad: Boarding pass FFFBBBLRRR has column letter L at index 6 in the row part"
+                    .into()
+            )
+        );
+
+        assert_eq!(
+            layout.decode_all("FFFBBBRFRR"),
+            Err(
+                "Seat FFFBBBRFRR at line 1 is bad: Boarding pass FFFBBBRFRR has column letter R at index 6 in the row part"
+                    .into()
+            )
+        );
+
+        // Letters that do not fit a byte take the slow path
+        let layout: Layout = "8 ↑↓ 2 LR".parse().unwrap();
+        assert_eq!(layout.decode_all("↓↑↓R"), Ok(vec![Seat::new(5, 1)]));
+        assert_eq!(
+            layout.decode_all("↓↑↓R\nLLL"),
+            Err("Seat LLL at line 2 is bad: Boarding pass LLL has 3 letters, expected 4".into())
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn decoders_agree(pass in "[FBLRX]{0,12}") {
+            let layout = Layout::default();
+
+            prop_assert_eq!(layout.decode(&pass).ok(), layout.parse_seat(&pass).ok());
+        }
+
+        #[test]
+        fn decoders_agree_on_any_layout(
+            row_bits in 0..8u32,
+            column_bits in 0..5u32,
+            pass in "[UDlh]{0,14}",
+        ) {
+            let layout: Layout = format!("{} UD {} lh", 1 << row_bits, 1 << column_bits)
+                .parse()
+                .unwrap();
+
+            prop_assert_eq!(layout.decode(&pass).ok(), layout.parse_seat(&pass).ok());
+        }
+
+        #[test]
+        fn bulk_decoding_is_decoding_every_pass(passes in prop::collection::vec("[FB]{7}[LR]{3}", 0..50)) {
+            let layout = Layout::default();
+            let seats: Vec<Seat> = passes
+                .iter()
+                .map(|pass| layout.decode(pass).unwrap())
+                .collect();
+
+            prop_assert_eq!(layout.decode_all(&passes.join("\n")), Ok(seats));
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::types::{Dimension, Layout, Seat};
+
+impl Dimension {
+    /// Letters that pick `position`, most significant half first
+    fn encode(&self, position: usize) -> impl Iterator<Item = char> + '_ {
+        (0..self.code_length()).rev().map(move |bit| {
+            if position >> bit & 1 == 1 {
+                self.upper
+            } else {
+                self.lower
+            }
+        })
+    }
+}
+
+impl Layout {
+    /// Seats on the plane, IDs go from zero up to this number
+    pub fn seat_count(&self) -> usize {
+        self.rows.size * self.columns.size
+    }
+
+    /// Inverse of `seat_id`
+    pub fn seat_from_id(&self, id: usize) -> Result<Seat, String> {
+        if id >= self.seat_count() {
+            return Err(format!(
+                "Seat ID {} is out of the plane, IDs go up to {}",
+                id,
+                self.seat_count() - 1
+            ));
+        }
+
+        Ok(Seat::new(id / self.columns.size, id % self.columns.size))
+    }
+
+    /// Canonical boarding pass of the seat: row letters, then column letters
+    pub fn encode(&self, seat: &Seat) -> Result<String, String> {
+        if seat.row() >= self.rows.size || seat.column() >= self.columns.size {
+            return Err(format!(
+                "Seat row {} column {} is out of the plane, it has {} rows and {} columns",
+                seat.row(),
+                seat.column(),
+                self.rows.size,
+                self.columns.size
+            ));
+        }
+
+        Ok(self
+            .rows
+            .encode(seat.row())
+            .chain(self.columns.encode(seat.column()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use test_case::test_case;
+
+    #[test_case(Seat::new(70, 7) => "BFFFBBFRRR")]
+    #[test_case(Seat::new(14, 7) => "FFFBBBFRRR")]
+    #[test_case(Seat::new(102, 4) => "BBFFBBFRLL")]
+    #[test_case(Seat::new(0, 0) => "FFFFFFFLLL")]
+    #[test_case(Seat::new(127, 7) => "BBBBBBBRRR")]
+    #[test_case(Seat::new(128, 0) => "row 128 column 0" ; "seat out of the plane")]
+    fn seat_to_string(seat: Seat) -> String {
+        seat.to_string()
+    }
+
+    #[test_case(Seat::new(3, 1) => Ok("FFFFFFBBLLLH".to_string()))]
+    #[test_case(Seat::new(256, 1) => Err("Seat row 256 column 1 is out of the plane, it has 256 rows and 16 columns".to_string()))]
+    #[test_case(Seat::new(0, 16) => Err("Seat row 0 column 16 is out of the plane, it has 256 rows and 16 columns".to_string()))]
+    fn encode_with_layout(seat: Seat) -> Result<String, String> {
+        let layout: Layout = "256 FB 16 LH".parse().unwrap();
+        layout.encode(&seat)
+    }
+
+    #[test_case(567 => Ok(Seat::new(70, 7)))]
+    #[test_case(0 => Ok(Seat::new(0, 0)))]
+    #[test_case(1023 => Ok(Seat::new(127, 7)))]
+    #[test_case(1024 => Err("Seat ID 1024 is out of the plane, IDs go up to 1023".to_string()))]
+    fn seat_from_id(id: usize) -> Result<Seat, String> {
+        Layout::default().seat_from_id(id)
+    }
+
+    #[test]
+    fn every_seat_of_plane_round_trips() {
+        let layout = Layout::default();
+
+        for id in 0..layout.seat_count() {
+            let seat = Layout::default().seat_from_id(id).unwrap();
+            let pass = seat.to_string();
+
+            pretty_assertions::assert_eq!(layout.seat_id(&seat), id);
+            pretty_assertions::assert_eq!(pass.parse(), Ok(seat), "{}", pass);
+            pretty_assertions::assert_eq!(layout.decode(&pass), Ok(seat), "{}", pass);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn encode_decode_round_trip(id in 0..1024usize) {
+            let seat = Layout::default().seat_from_id(id).unwrap();
+
+            prop_assert_eq!(seat.to_string().parse::<Seat>(), Ok(seat));
+            prop_assert_eq!(Layout::default().seat_from_id(Layout::default().seat_id(&seat)), Ok(seat));
+        }
+
+        #[test]
+        fn decode_encode_round_trip(pass in "[FB]{7}[LR]{3}") {
+            prop_assert_eq!(pass.parse::<Seat>().unwrap().to_string(), pass);
+        }
+
+        #[test]
+        fn round_trip_on_any_layout(row_bits in 0..9u32, column_bits in 0..5u32, id in 0..usize::MAX) {
+            let layout: Layout = format!("{} UD {} lh", 1 << row_bits, 1 << column_bits)
+                .parse()
+                .unwrap();
+            let seat = layout.seat_from_id(id % layout.seat_count()).unwrap();
+
+            prop_assert_eq!(layout.parse_seat(&layout.encode(&seat).unwrap()), Ok(seat));
+        }
+    }
+}
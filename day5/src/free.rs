@@ -0,0 +1,161 @@
+use crate::plane::Plane;
+use crate::types::Seat;
+use std::{collections::HashSet, fmt, ops::Range};
+
+/// Seat IDs without a boarding pass, split by where they are on the plane
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FreeSeats {
+    /// In front of the first occupied seat, most likely the plane does not have them
+    pub front: Range<usize>,
+    /// Behind the last occupied seat, most likely the plane does not have them
+    pub back: Range<usize>,
+    /// Between the first and the last occupied seats, blocked and nonexistent ones are skipped
+    pub interior: Vec<usize>,
+    /// Interior IDs with both neighbor IDs occupied
+    pub candidates: Vec<usize>,
+}
+
+impl FreeSeats {
+    pub fn new(plane: &Plane, seats: &[Seat]) -> Self {
+        let layout = &plane.layout;
+        let occupied: HashSet<usize> = seats.iter().map(|seat| layout.seat_id(seat)).collect();
+
+        let (lowest, highest) = match (occupied.iter().min(), occupied.iter().max()) {
+            (Some(lowest), Some(highest)) => (*lowest, *highest),
+            _ => {
+                return FreeSeats {
+                    front: 0..layout.seat_count(),
+                    back: layout.seat_count()..layout.seat_count(),
+                    interior: vec![],
+                    candidates: vec![],
+                }
+            }
+        };
+
+        let interior: Vec<usize> = (lowest..highest)
+            .filter(|id| !occupied.contains(id) && plane.is_available(*id))
+            .collect();
+        let candidates = interior
+            .iter()
+            .copied()
+            .filter(|id| occupied.contains(&(id - 1)) && occupied.contains(&(id + 1)))
+            .collect();
+
+        FreeSeats {
+            front: 0..lowest,
+            back: highest + 1..layout.seat_count(),
+            interior,
+            candidates,
+        }
+    }
+
+    /// The only candidate, the seat of the one passenger without a boarding pass
+    pub fn my_seat(&self) -> Result<usize, MySeatError> {
+        match self.candidates[..] {
+            [id] => Ok(id),
+            [] => Err(MySeatError::NoCandidate {
+                interior: self.interior.clone(),
+            }),
+            _ => Err(MySeatError::Ambiguous(self.candidates.clone())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MySeatError {
+    /// No free seat has both neighbors occupied, `interior` are the free seats there are
+    NoCandidate { interior: Vec<usize> },
+    /// More than one free seat has both neighbors occupied
+    Ambiguous(Vec<usize>),
+}
+
+fn join(ids: &[usize]) -> String {
+    ids.iter()
+        .map(usize::to_string)
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl fmt::Display for MySeatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MySeatError::NoCandidate { interior } if interior.is_empty() => {
+                write!(f, "No free seat between occupied ones")
+            }
+            MySeatError::NoCandidate { interior } => write!(
+                f,
+                "No free seat has both neighbors occupied, free seats: {}",
+                join(interior)
+            ),
+            MySeatError::Ambiguous(candidates) => {
+                write!(f, "More than one seat could be mine: {}", join(candidates))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn free_seats_of(plane: &str, ids: &[usize]) -> FreeSeats {
+        let plane = Plane::parse("8 FB 4 LR".parse().unwrap(), plane).unwrap();
+        let seats: Vec<Seat> = ids
+            .iter()
+            .map(|id| plane.layout.seat_from_id(*id).unwrap())
+            .collect();
+
+        FreeSeats::new(&plane, &seats)
+    }
+
+    fn free_seats(ids: &[usize]) -> FreeSeats {
+        free_seats_of("", ids)
+    }
+
+    #[test]
+    fn free_seats_by_place() {
+        pretty_assertions::assert_eq!(
+            free_seats(&[5, 6, 8, 9, 11, 12, 15, 16]),
+            FreeSeats {
+                front: 0..5,
+                back: 17..32,
+                interior: vec![7, 10, 13, 14],
+                candidates: vec![7, 10],
+            }
+        );
+        pretty_assertions::assert_eq!(
+            free_seats(&[]),
+            FreeSeats {
+                front: 0..32,
+                back: 32..32,
+                interior: vec![],
+                candidates: vec![],
+            }
+        );
+    }
+
+    #[test_case(&[5, 6, 8, 9] => Ok(7))]
+    #[test_case(&[5, 6, 8, 9, 12] => Ok(7) ; "gap of two is not a candidate")]
+    #[test_case(&[5, 6, 8, 9, 11] => Err(MySeatError::Ambiguous(vec![7, 10])))]
+    #[test_case(&[5, 6, 9] => Err(MySeatError::NoCandidate { interior: vec![7, 8] }))]
+    #[test_case(&[5, 6, 7] => Err(MySeatError::NoCandidate { interior: vec![] }))]
+    #[test_case(&[] => Err(MySeatError::NoCandidate { interior: vec![] }))]
+    fn my_seat(ids: &[usize]) -> Result<usize, MySeatError> {
+        free_seats(ids).my_seat()
+    }
+
+    #[test_case("blocked 2 2" => Ok(7) ; "blocked seat is skipped")]
+    #[test_case("nonexistent 2 2" => Ok(7) ; "nonexistent seat is skipped")]
+    #[test_case("premium 2 2" => Err(MySeatError::Ambiguous(vec![7, 10])) ; "premium seat is free")]
+    fn my_seat_on_plane(plane: &str) -> Result<usize, MySeatError> {
+        free_seats_of(plane, &[5, 6, 8, 9, 11]).my_seat()
+    }
+
+    #[test_case(MySeatError::NoCandidate { interior: vec![] } => "No free seat between occupied ones")]
+    #[test_case(MySeatError::NoCandidate { interior: vec![7, 8] } => "No free seat has both neighbors occupied, free seats: 7, 8")]
+    #[test_case(MySeatError::Ambiguous(vec![7, 10]) => "More than one seat could be mine: 7, 10")]
+    fn my_seat_error_to_string(err: MySeatError) -> String {
+        err.to_string()
+    }
+}
@@ -1,37 +1,314 @@
-use crate::types::Seat;
-use std::collections::HashSet;
-use std::fs::read_to_string;
+use crate::free::FreeSeats;
+use crate::plane::Plane;
+use crate::render::SeatMap;
+use crate::simulate::Strategy;
+use crate::types::{Layout, Seat};
+use crate::validate::Issue;
+use std::env;
+use std::fs::{self, read_to_string};
+use std::io::{self, IsTerminal};
+use std::process;
+use std::time::Instant;
 
+mod decode;
+mod encode;
+mod free;
+mod plane;
+mod render;
+mod simulate;
 mod types;
+mod validate;
 
-fn read_input(filename: &str) -> Vec<Seat> {
-    read_to_string(filename)
-        .expect("No file named \"input.txt\" found")
-        .split_whitespace()
-        .enumerate()
-        .map(|(line, seat_as_string)| {
-            seat_as_string.parse().unwrap_or_else(|err| {
-                panic!("Seat {} at line {} is bad: {}", seat_as_string, line, err)
-            })
-        })
-        .collect()
+const INPUT_FILENAME: &str = "input.txt";
+
+/// Input repeats of the benchmark, when not given
+const BENCH_REPEAT: usize = 1000;
+
+/// Seed of the boarding simulation, when not given
+const SIMULATE_SEED: u64 = 2020;
+
+const USAGE: &str = "Usage: day5 [bench [repeat] | map [output.txt|output.svg] \
+                     | simulate [random|back-to-front|window-middle-aisle|groups [output.txt]]] \
+                     [--layout \"<rows> <lower><upper> <columns> <lower><upper>\"] \
+                     [--plane <file>] [--seed N] [--passengers N]";
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    /// Find the highest seat ID and my seat
+    Solve,
+    /// Compare decoders on the input repeated many times
+    Bench(usize),
+    /// Draw the seats to the terminal, or to a text or SVG file
+    Map(Option<String>),
+    /// Compare conflicts of every strategy, or write the boarding passes of one
+    /// strategy to the terminal or to a file
+    Simulate(Option<(Strategy, Option<String>)>),
 }
 
-fn main() {
-    let seats = read_input("input.txt");
+#[derive(Debug)]
+struct Options {
+    command: Command,
+    layout: Layout,
+    /// Nonexistent, blocked and premium seats, every seat is a normal one when not given
+    plane: Option<String>,
+    seed: u64,
+    /// Every seat of the plane is taken, when not given
+    passengers: Option<usize>,
+}
+
+impl Options {
+    fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            command: Command::Solve,
+            layout: Layout::default(),
+            plane: None,
+            seed: SIMULATE_SEED,
+            passengers: None,
+        };
+        let mut args = args.into_iter().peekable();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "bench" if options.command == Command::Solve => {
+                    let repeat = match args.peek() {
+                        Some(repeat) if !repeat.starts_with("--") => args
+                            .next()
+                            .and_then(|repeat| repeat.parse().ok())
+                            .ok_or(USAGE)?,
+                        _ => BENCH_REPEAT,
+                    };
+                    options.command = Command::Bench(repeat);
+                }
+                "map" if options.command == Command::Solve => {
+                    let output = match args.peek() {
+                        Some(output) if !output.starts_with("--") => args.next(),
+                        _ => None,
+                    };
+                    options.command = Command::Map(output);
+                }
+                "simulate" if options.command == Command::Solve => {
+                    let strategy = match args.peek() {
+                        Some(strategy) if !strategy.starts_with("--") => {
+                            let strategy = args.next().ok_or(USAGE)?.parse()?;
+                            let output = match args.peek() {
+                                Some(output) if !output.starts_with("--") => args.next(),
+                                _ => None,
+                            };
+                            Some((strategy, output))
+                        }
+                        _ => None,
+                    };
+                    options.command = Command::Simulate(strategy);
+                }
+                "--layout" => options.layout = args.next().ok_or(USAGE)?.parse()?,
+                "--plane" => options.plane = Some(args.next().ok_or(USAGE)?),
+                "--seed" => {
+                    options.seed = args
+                        .next()
+                        .and_then(|seed| seed.parse().ok())
+                        .ok_or(USAGE)?
+                }
+                "--passengers" => {
+                    options.passengers = Some(
+                        args.next()
+                            .and_then(|passengers| passengers.parse().ok())
+                            .ok_or(USAGE)?,
+                    )
+                }
+                _ => return Err(USAGE.into()),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Every bad and duplicate pass is reported, not only the first one
+fn read_input(filename: &str, plane: &Plane) -> Result<Vec<Seat>, String> {
+    let text =
+        read_to_string(filename).map_err(|err| format!("Can not read {}: {}", filename, err))?;
+    let validated = plane.validate(&text);
 
-    let highest_seat_id = seats.iter().map(Seat::id).max().unwrap();
+    if !validated.issues.is_empty() {
+        let issues: Vec<String> = validated.issues.iter().map(Issue::to_string).collect();
+        return Err(issues.join("\n"));
+    }
+
+    Ok(validated.seats)
+}
+
+fn solve(plane: &Plane) -> Result<(), String> {
+    let layout = &plane.layout;
+    let seats = read_input(INPUT_FILENAME, plane)?;
+    let seat_id = |seat: &Seat| layout.seat_id(seat);
+
+    let highest_seat_id = seats.iter().map(seat_id).max().unwrap();
     println!(
         "The highest ID on a boarding pass is: {:?}",
         highest_seat_id
     );
 
-    let lowest_seat_id = seats.iter().map(Seat::id).min().unwrap();
-    let listed_seat_ids: HashSet<_> = seats.iter().map(Seat::id).collect();
-
-    let my_id = (lowest_seat_id..=highest_seat_id)
-        .find(|id| !listed_seat_ids.contains(id))
-        .unwrap();
+    let my_id = FreeSeats::new(plane, &seats)
+        .my_seat()
+        .map_err(|err| err.to_string())?;
 
     println!("My id is {}", my_id);
+    println!(
+        "My boarding pass is {}",
+        layout.encode(&layout.seat_from_id(my_id)?)?
+    );
+
+    Ok(())
+}
+
+/// Decode the input repeated `repeat` times with every decoder and print throughput
+fn bench(plane: &Plane, repeat: usize) -> Result<(), String> {
+    let layout = &plane.layout;
+    let input = read_to_string(INPUT_FILENAME)
+        .map_err(|err| format!("Can not read {}: {}", INPUT_FILENAME, err))?;
+    // Passes are repeated, not the text, so the last pass never runs into the first one
+    let passes: Vec<&str> = input
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .repeat(repeat);
+    let text = passes.join("\n");
+
+    let decoders: &[(&str, &dyn Fn() -> usize)] = &[
+        ("range halving", &|| {
+            passes
+                .iter()
+                .filter_map(|pass| layout.parse_seat(pass).ok())
+                .count()
+        }),
+        ("bits", &|| {
+            passes
+                .iter()
+                .filter_map(|pass| layout.decode(pass).ok())
+                .count()
+        }),
+        ("bulk bits", &|| {
+            layout.decode_all(&text).map_or(0, |seats| seats.len())
+        }),
+    ];
+
+    for (name, decode) in decoders {
+        let start = Instant::now();
+        let seats = decode();
+        let seconds = start.elapsed().as_secs_f64();
+
+        println!(
+            "{:<13}: {} passes in {:.3}s, {:.0} passes/s",
+            name,
+            seats,
+            seconds,
+            seats as f64 / seconds
+        );
+    }
+
+    Ok(())
+}
+
+fn draw_map(plane: &Plane, output: Option<&str>) -> Result<(), String> {
+    let seat_map = SeatMap::new(plane, &read_input(INPUT_FILENAME, plane)?);
+
+    match output {
+        // Colors only go to a terminal, not to a pipe or a file
+        None => {
+            print!("{}", seat_map.to_text(io::stdout().is_terminal()));
+            Ok(())
+        }
+        Some(filename) => {
+            let text = if filename.ends_with(".svg") {
+                seat_map.to_svg()
+            } else {
+                seat_map.to_text(false)
+            };
+            fs::write(filename, text).map_err(|err| format!("Can not write {}: {}", filename, err))
+        }
+    }
+}
+
+fn conflicts_report(layout: &Layout, strategy: Strategy, seats: &[Seat]) -> String {
+    let conflicts = layout.conflicts(seats);
+
+    format!(
+        "{:<19}: {} passengers, {} conflicts ({} in the aisle, {} in seats)",
+        strategy,
+        seats.len(),
+        conflicts.total(),
+        conflicts.aisle,
+        conflicts.seat
+    )
+}
+
+/// Without a strategy compare conflicts of every one, with a strategy write its boarding
+/// passes in the input format, one per line in boarding order
+fn simulate(
+    plane: &Plane,
+    options: &Options,
+    strategy: Option<(Strategy, Option<&str>)>,
+) -> Result<(), String> {
+    let layout = &plane.layout;
+    let passengers = options.passengers.unwrap_or_else(|| layout.seat_count());
+
+    let (strategy, output) = match strategy {
+        Some(strategy) => strategy,
+        None => {
+            for strategy in &Strategy::ALL {
+                let seats = plane.simulate(*strategy, passengers, options.seed);
+                println!("{}", conflicts_report(layout, *strategy, &seats));
+            }
+            return Ok(());
+        }
+    };
+
+    let seats = plane.simulate(strategy, passengers, options.seed);
+    let passes: String = seats
+        .iter()
+        .map(|seat| Ok(layout.encode(seat)? + "\n"))
+        .collect::<Result<String, String>>()?;
+
+    match output {
+        Some(filename) => {
+            fs::write(filename, passes)
+                .map_err(|err| format!("Can not write {}: {}", filename, err))?;
+            println!("{}", conflicts_report(layout, strategy, &seats));
+        }
+        // Passes are the output, the report goes aside so they can be piped
+        None => {
+            print!("{}", passes);
+            eprintln!("{}", conflicts_report(layout, strategy, &seats));
+        }
+    }
+
+    Ok(())
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let plane = match &options.plane {
+        Some(filename) => Plane::load(options.layout, filename)?,
+        None => Plane::new(options.layout),
+    };
+
+    match &options.command {
+        Command::Solve => solve(&plane),
+        Command::Bench(repeat) => bench(&plane, *repeat),
+        Command::Map(output) => draw_map(&plane, output.as_deref()),
+        Command::Simulate(strategy) => simulate(
+            &plane,
+            options,
+            strategy
+                .as_ref()
+                .map(|(strategy, output)| (*strategy, output.as_deref())),
+        ),
+    }
+}
+
+fn main() {
+    let result = Options::from_args(env::args().skip(1)).and_then(|options| run(&options));
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
 }
@@ -0,0 +1,195 @@
+use crate::types::{Layout, Seat};
+use std::{fs::read_to_string, ops::RangeInclusive, str::FromStr, sync::OnceLock};
+
+const STANDARD_PLANE: &str = include_str!("../planes/standard.plane");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SeatKind {
+    Normal,
+    /// Plane does not have the seat, e.g. row 13 on some airlines
+    Nonexistent,
+    /// Seat exists but is not sold
+    Blocked,
+    Premium,
+}
+
+impl FromStr for SeatKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nonexistent" => Ok(SeatKind::Nonexistent),
+            "blocked" => Ok(SeatKind::Blocked),
+            "premium" => Ok(SeatKind::Premium),
+            case => Err(format!("Seat kind is not recognized: {}", case)),
+        }
+    }
+}
+
+/// Number or inclusive range of numbers below `size`, e.g. "13" or "10-12"
+fn positions(s: &str, size: usize) -> Result<RangeInclusive<usize>, String> {
+    let number = |n: &str| {
+        n.parse::<usize>()
+            .map_err(|_| format!("Position is not a number: {}", n))
+    };
+    let (start, end) = match s.split_once('-') {
+        Some((start, end)) => (number(start)?, number(end)?),
+        None => (number(s)?, number(s)?),
+    };
+
+    if start > end {
+        return Err(format!("Range {} is backwards", s));
+    }
+    if end >= size {
+        return Err(format!(
+            "Position {} is out of the plane, size is {}",
+            end, size
+        ));
+    }
+
+    Ok(start..=end)
+}
+
+/// Layout of the plane and what kind every seat of it is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Plane {
+    pub layout: Layout,
+    /// Indexed by seat ID
+    kinds: Vec<SeatKind>,
+}
+
+impl Plane {
+    /// Every seat of the layout is a normal one
+    pub fn new(layout: Layout) -> Self {
+        Plane {
+            layout,
+            kinds: vec![SeatKind::Normal; layout.seat_count()],
+        }
+    }
+
+    /// Standard layout with the seats bundled with the program
+    pub fn standard() -> &'static Plane {
+        static PLANE: OnceLock<Plane> = OnceLock::new();
+
+        PLANE.get_or_init(|| {
+            Plane::parse(Layout::default(), STANDARD_PLANE).expect("Bundled plane is bad")
+        })
+    }
+
+    /// One mark per line, e.g. "blocked 40 3-4", blank lines and `#` comments are skipped
+    pub fn parse(layout: Layout, text: &str) -> Result<Self, String> {
+        let mut plane = Plane::new(layout);
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            plane
+                .mark(line)
+                .map_err(|err| format!("Plane line {} is bad: {}", index + 1, err))?;
+        }
+
+        Ok(plane)
+    }
+
+    pub fn load(layout: Layout, filename: &str) -> Result<Self, String> {
+        let text = read_to_string(filename)
+            .map_err(|err| format!("Can not read {}: {}", filename, err))?;
+
+        Plane::parse(layout, &text)
+    }
+
+    fn mark(&mut self, line: &str) -> Result<(), String> {
+        let (kind, rows, columns) = match line.split_whitespace().collect::<Vec<&str>>()[..] {
+            [kind, rows] => (kind, rows, None),
+            [kind, rows, columns] => (kind, rows, Some(columns)),
+            _ => return Err(format!("Mark is not recognized: {}", line)),
+        };
+        let kind: SeatKind = kind.parse()?;
+        let rows = positions(rows, self.layout.rows.size)?;
+        let columns = match columns {
+            Some(columns) => positions(columns, self.layout.columns.size)?,
+            None => 0..=self.layout.columns.size - 1,
+        };
+
+        for row in rows {
+            for column in columns.clone() {
+                let id = self.layout.seat_id(&Seat::new(row, column));
+                self.kinds[id] = kind;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn kind(&self, seat: &Seat) -> SeatKind {
+        self.kinds[self.layout.seat_id(seat)]
+    }
+
+    /// Seat can be sold to a passenger
+    pub fn is_available(&self, id: usize) -> bool {
+        matches!(self.kinds[id], SeatKind::Normal | SeatKind::Premium)
+    }
+
+    /// Boarding pass of a seat that the plane has
+    pub fn parse_seat(&self, s: &str) -> Result<Seat, String> {
+        let seat = self.layout.parse_seat(s)?;
+
+        match self.kind(&seat) {
+            SeatKind::Nonexistent => Err(format!(
+                "Boarding pass {} is row {} column {}, the plane does not have it",
+                s,
+                seat.row(),
+                seat.column()
+            )),
+            _ => Ok(seat),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn plane() -> Plane {
+        let text = "# Small plane\n\nnonexistent 3\nblocked 5 0-1\npremium 0-1\nblocked 1 3\n";
+
+        Plane::parse("8 FB 4 LR".parse().unwrap(), text).unwrap()
+    }
+
+    #[test_case(0, 0 => SeatKind::Premium)]
+    #[test_case(1, 3 => SeatKind::Blocked ; "later line wins")]
+    #[test_case(3, 2 => SeatKind::Nonexistent)]
+    #[test_case(5, 1 => SeatKind::Blocked)]
+    #[test_case(5, 2 => SeatKind::Normal)]
+    fn seat_kind(row: usize, column: usize) -> SeatKind {
+        plane().kind(&Seat::new(row, column))
+    }
+
+    #[test_case("FBBLR" => Err("Boarding pass FBBLR is row 3 column 1, the plane does not have it".to_string()))]
+    #[test_case("BFBLL" => Ok(Seat::new(5, 0)) ; "blocked seat exists")]
+    #[test_case("FBBLRR" => Err("Boarding pass FBBLRR has 6 letters, expected 5".to_string()))]
+    fn parse_seat(pass: &str) -> Result<Seat, String> {
+        plane().parse_seat(pass)
+    }
+
+    #[test_case("broken 3" => "Plane line 1 is bad: Seat kind is not recognized: broken")]
+    #[test_case("\nblocked 8" => "Plane line 2 is bad: Position 8 is out of the plane, size is 8")]
+    #[test_case("blocked 1 4" => "Plane line 1 is bad: Position 4 is out of the plane, size is 4")]
+    #[test_case("blocked 3-1" => "Plane line 1 is bad: Range 3-1 is backwards")]
+    #[test_case("blocked x" => "Plane line 1 is bad: Position is not a number: x")]
+    #[test_case("blocked" => "Plane line 1 is bad: Mark is not recognized: blocked")]
+    fn bad_plane(text: &str) -> String {
+        Plane::parse("8 FB 4 LR".parse().unwrap(), text).unwrap_err()
+    }
+
+    #[test]
+    fn standard_plane_has_every_seat() {
+        let plane = Plane::standard();
+
+        assert!((0..plane.layout.seat_count()).all(|id| plane.is_available(id)));
+    }
+}
@@ -0,0 +1,350 @@
+use crate::plane::{Plane, SeatKind};
+use crate::types::{Layout, Seat};
+use std::fmt::Write;
+
+/// Size of a seat in the SVG map, in pixels
+const SVG_CELL: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SeatState {
+    /// Somebody has a boarding pass for the seat
+    Occupied,
+    /// Free seat between the first and the last occupied ones
+    Empty,
+    /// Seat in front of the first or behind the last occupied one, the plane does not have it
+    Missing,
+    /// Plane file says the seat is not sold
+    Blocked,
+    /// Plane file says the plane does not have the seat, it is not drawn
+    Nonexistent,
+}
+
+impl SeatState {
+    fn letter(&self) -> char {
+        match self {
+            SeatState::Occupied => '#',
+            SeatState::Empty => '_',
+            SeatState::Missing => '-',
+            SeatState::Blocked => 'x',
+            SeatState::Nonexistent => ' ',
+        }
+    }
+
+    /// Terminal escape code of the letter color
+    fn color(&self) -> &'static str {
+        match self {
+            SeatState::Occupied => "\x1b[32m",
+            SeatState::Empty => "\x1b[1;33m",
+            SeatState::Missing => "\x1b[2m",
+            SeatState::Blocked => "\x1b[31m",
+            SeatState::Nonexistent => "",
+        }
+    }
+
+    fn fill(&self) -> &'static str {
+        match self {
+            SeatState::Occupied => "#2e7d32",
+            SeatState::Empty => "#fbc02d",
+            SeatState::Missing => "#e0e0e0",
+            SeatState::Blocked => "#c62828",
+            SeatState::Nonexistent => "none",
+        }
+    }
+
+    /// Seat counts toward occupancy, blocked and nonexistent seats are never sold
+    fn is_for_sale(&self) -> bool {
+        !matches!(self, SeatState::Blocked | SeatState::Nonexistent)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RowStats {
+    pub occupied: usize,
+    pub seats: usize,
+}
+
+impl RowStats {
+    /// Rows without seats for sale are empty
+    pub fn occupancy(&self) -> f64 {
+        if self.seats == 0 {
+            return 0.0;
+        }
+        100.0 * self.occupied as f64 / self.seats as f64
+    }
+}
+
+/// State of every seat of the plane
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SeatMap {
+    layout: Layout,
+    /// Indexed by seat ID
+    states: Vec<SeatState>,
+}
+
+impl SeatMap {
+    /// Seats are marked with their kind on `plane`, occupied seats stay occupied
+    pub fn new(plane: &Plane, seats: &[Seat]) -> Self {
+        let layout = &plane.layout;
+        let mut states = vec![SeatState::Missing; layout.seat_count()];
+        let ids = seats.iter().map(|seat| layout.seat_id(seat));
+
+        if let (Some(lowest), Some(highest)) = (ids.clone().min(), ids.clone().max()) {
+            for state in &mut states[lowest..=highest] {
+                *state = SeatState::Empty;
+            }
+            for id in ids {
+                states[id] = SeatState::Occupied;
+            }
+        }
+
+        for (id, state) in states.iter_mut().enumerate() {
+            let seat = layout.seat_from_id(id).expect("IDs are on the plane");
+            match plane.kind(&seat) {
+                _ if *state == SeatState::Occupied => {}
+                SeatKind::Blocked => *state = SeatState::Blocked,
+                SeatKind::Nonexistent => *state = SeatState::Nonexistent,
+                SeatKind::Normal | SeatKind::Premium => {}
+            }
+        }
+
+        SeatMap {
+            layout: *layout,
+            states,
+        }
+    }
+
+    pub fn row(&self, row: usize) -> &[SeatState] {
+        let columns = self.layout.columns.size;
+        &self.states[row * columns..(row + 1) * columns]
+    }
+
+    pub fn row_stats(&self, row: usize) -> RowStats {
+        let row = self.row(row);
+
+        RowStats {
+            occupied: row
+                .iter()
+                .filter(|state| **state == SeatState::Occupied)
+                .count(),
+            seats: row.iter().filter(|state| state.is_for_sale()).count(),
+        }
+    }
+
+    /// Rows without a single occupied seat at the front and at the back of the plane
+    pub fn empty_rows(&self) -> (usize, usize) {
+        let is_empty = |row: &usize| self.row_stats(*row).occupied == 0;
+        let rows = 0..self.layout.rows.size;
+
+        let front = rows.clone().take_while(is_empty).count();
+        if front == self.layout.rows.size {
+            return (front, 0);
+        }
+
+        (front, rows.rev().take_while(is_empty).count())
+    }
+
+    /// Column the aisle goes in front of, a plane with one column has no aisle
+    fn aisle(&self) -> Option<usize> {
+        Some(self.layout.columns.size / 2).filter(|aisle| *aisle > 0)
+    }
+
+    fn occupied(&self) -> usize {
+        self.states
+            .iter()
+            .filter(|state| **state == SeatState::Occupied)
+            .count()
+    }
+
+    /// One line per row with the aisle in the middle and occupancy of the row,
+    /// seats are colored with terminal escape codes if `color` is set
+    pub fn to_text(&self, color: bool) -> String {
+        let label_width = (self.layout.rows.size - 1).to_string().len();
+        let mut text = String::new();
+
+        for row in 0..self.layout.rows.size {
+            let mut line = format!("{:>width$} ", row, width = label_width);
+            for (column, state) in self.row(row).iter().enumerate() {
+                if Some(column) == self.aisle() {
+                    line.push(' ');
+                }
+                if color {
+                    let _ = write!(line, "{}{}\x1b[0m", state.color(), state.letter());
+                } else {
+                    line.push(state.letter());
+                }
+            }
+            let _ = writeln!(text, "{} {:>4.0}%", line, self.row_stats(row).occupancy());
+        }
+
+        let (front, back) = self.empty_rows();
+        let _ = writeln!(text, "Empty rows at front: {}, at back: {}", front, back);
+        let _ = writeln!(
+            text,
+            "Occupied: {} of {}",
+            self.occupied(),
+            self.states
+                .iter()
+                .filter(|state| state.is_for_sale())
+                .count()
+        );
+
+        text
+    }
+
+    /// Seats as squares, occupancy of every row on the right
+    pub fn to_svg(&self) -> String {
+        let aisles = self.aisle().is_some() as usize;
+        let seats_width = (self.layout.columns.size + aisles) * SVG_CELL;
+        let width = seats_width + 4 * SVG_CELL;
+        let height = self.layout.rows.size * SVG_CELL;
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"monospace\" font-size=\"{}\">\n",
+            width,
+            height,
+            SVG_CELL - 2
+        );
+        for row in 0..self.layout.rows.size {
+            let y = row * SVG_CELL;
+            for (column, state) in self.row(row).iter().enumerate() {
+                if *state == SeatState::Nonexistent {
+                    continue;
+                }
+                let after_aisle = self.aisle().is_some_and(|aisle| column >= aisle);
+                let x = (column + after_aisle as usize) * SVG_CELL;
+                let _ = writeln!(
+                    svg,
+                    "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+                    x + 1,
+                    y + 1,
+                    SVG_CELL - 2,
+                    SVG_CELL - 2,
+                    state.fill()
+                );
+            }
+            let _ = writeln!(
+                svg,
+                "  <text x=\"{}\" y=\"{}\">{:.0}%</text>",
+                seats_width + SVG_CELL / 2,
+                y + SVG_CELL - 2,
+                self.row_stats(row).occupancy()
+            );
+        }
+        svg.push_str("</svg>\n");
+
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn seat_map() -> SeatMap {
+        let layout: Layout = "8 FB 4 LR".parse().unwrap();
+        let seats: Vec<Seat> = [9, 10, 12, 13, 14, 15, 16, 17, 19, 20]
+            .iter()
+            .map(|id| layout.seat_from_id(*id).unwrap())
+            .collect();
+
+        SeatMap::new(&Plane::new(layout), &seats)
+    }
+
+    #[test]
+    fn seat_map_to_text() {
+        pretty_assertions::assert_eq!(
+            seat_map().to_text(false),
+            [
+                "0 -- --    0%",
+                "1 -- --    0%",
+                "2 -# #_   50%",
+                "3 ## ##  100%",
+                "4 ## _#   75%",
+                "5 #- --   25%",
+                "6 -- --    0%",
+                "7 -- --    0%",
+                "Empty rows at front: 2, at back: 2",
+                "Occupied: 10 of 32",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test_case(0 => (0, 4))]
+    #[test_case(2 => (2, 4))]
+    #[test_case(4 => (3, 4))]
+    fn row_stats(row: usize) -> (usize, usize) {
+        let stats = seat_map().row_stats(row);
+        (stats.occupied, stats.seats)
+    }
+
+    #[test]
+    fn empty_plane() {
+        let layout: Layout = "8 FB 4 LR".parse().unwrap();
+
+        pretty_assertions::assert_eq!(SeatMap::new(&Plane::new(layout), &[]).empty_rows(), (8, 0));
+    }
+
+    #[test]
+    fn seat_map_to_svg() {
+        let svg = seat_map().to_svg();
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        pretty_assertions::assert_eq!(svg.matches("<rect ").count(), 32);
+        assert!(
+            svg.contains("<rect x=\"37\" y=\"49\" width=\"10\" height=\"10\" fill=\"#fbc02d\"/>")
+        );
+        assert!(svg.contains(">75%</text>"));
+    }
+
+    #[test]
+    fn seats_of_plane_file() {
+        let plane = Plane::parse(
+            "8 FB 4 LR".parse().unwrap(),
+            "nonexistent 3\nblocked 2 3\nblocked 4 0-1",
+        )
+        .unwrap();
+        let seats: Vec<Seat> = [9, 10, 18, 19, 20]
+            .iter()
+            .map(|id| plane.layout.seat_from_id(*id).unwrap())
+            .collect();
+        let seat_map = SeatMap::new(&plane, &seats);
+
+        pretty_assertions::assert_eq!(
+            seat_map.to_text(false),
+            [
+                "0 -- --    0%",
+                "1 -- --    0%",
+                "2 -# #x   67%",
+                "3          0%",
+                "4 xx ##  100%",
+                "5 #- --   25%",
+                "6 -- --    0%",
+                "7 -- --    0%",
+                "Empty rows at front: 2, at back: 2",
+                "Occupied: 5 of 25",
+                "",
+            ]
+            .join("\n")
+        );
+        // Nonexistent seats are not drawn
+        pretty_assertions::assert_eq!(seat_map.to_svg().matches("<rect ").count(), 28);
+    }
+
+    #[test]
+    fn one_column_has_no_aisle() {
+        let layout: Layout = "2 FB 1 LR".parse().unwrap();
+        let seat_map = SeatMap::new(&Plane::new(layout), &[Seat::new(1, 0)]);
+
+        pretty_assertions::assert_eq!(
+            seat_map.to_text(false),
+            "0 -    0%\n1 #  100%\nEmpty rows at front: 1, at back: 0\nOccupied: 1 of 2\n"
+        );
+        let svg = seat_map.to_svg();
+        assert!(svg.contains("width=\"60\""));
+        assert!(svg.contains("<rect x=\"1\" y=\"13\""));
+    }
+}
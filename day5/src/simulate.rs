@@ -0,0 +1,307 @@
+use crate::plane::Plane;
+use crate::types::{Layout, Seat};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::{fmt, str::FromStr};
+
+/// Most passengers that travel together
+const MAX_GROUP: usize = 3;
+
+/// Order in which passengers get seats and board the plane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Strategy {
+    /// Random seats, random boarding order
+    Random,
+    /// Back rows board first
+    BackToFront,
+    /// Window seats board first, aisle seats last
+    WindowMiddleAisle,
+    /// Passengers travelling together sit next to each other and board together
+    Groups,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 4] = [
+        Strategy::Random,
+        Strategy::BackToFront,
+        Strategy::WindowMiddleAisle,
+        Strategy::Groups,
+    ];
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Strategy::Random),
+            "back-to-front" => Ok(Strategy::BackToFront),
+            "window-middle-aisle" => Ok(Strategy::WindowMiddleAisle),
+            "groups" => Ok(Strategy::Groups),
+            case => Err(format!("Strategy is not recognized: {}", case)),
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Strategy::Random => "random",
+            Strategy::BackToFront => "back-to-front",
+            Strategy::WindowMiddleAisle => "window-middle-aisle",
+            Strategy::Groups => "groups",
+        };
+        f.pad(name)
+    }
+}
+
+/// Times passengers get in the way of each other while boarding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Conflicts {
+    /// Passenger has to wait in the aisle behind the one that boarded before
+    pub aisle: usize,
+    /// Seated passenger has to stand up to let somebody to a seat closer to the window
+    pub seat: usize,
+}
+
+impl Conflicts {
+    pub fn total(&self) -> usize {
+        self.aisle + self.seat
+    }
+}
+
+impl Layout {
+    /// Seats between this one and the aisle, the aisle is in the middle of the row
+    fn aisle_distance(&self, seat: &Seat) -> usize {
+        let aisle = self.columns.size / 2;
+
+        if seat.column() < aisle {
+            aisle - 1 - seat.column()
+        } else {
+            seat.column() - aisle
+        }
+    }
+
+    /// Half of the row on one side of the aisle
+    fn side(&self, seat: &Seat) -> (usize, bool) {
+        (seat.row(), seat.column() < self.columns.size / 2)
+    }
+
+    /// Count conflicts of passengers boarding in the order of `seats` through the front door
+    pub fn conflicts(&self, seats: &[Seat]) -> Conflicts {
+        let mut conflicts = Conflicts::default();
+        let mut seated: Vec<Seat> = vec![];
+
+        for (index, seat) in seats.iter().enumerate() {
+            if index > 0 && seat.row() > seats[index - 1].row() {
+                conflicts.aisle += 1;
+            }
+
+            conflicts.seat += seated
+                .iter()
+                .filter(|other| {
+                    self.side(other) == self.side(seat)
+                        && self.aisle_distance(other) < self.aisle_distance(seat)
+                })
+                .count();
+            seated.push(*seat);
+        }
+
+        conflicts
+    }
+}
+
+impl Plane {
+    fn is_seat_available(&self, seat: &Seat) -> bool {
+        self.is_available(self.layout.seat_id(seat))
+    }
+
+    /// Seat `passengers` people with `strategy`, seats are in boarding order.
+    /// Only available seats are given out, see `Plane::is_available`.
+    ///
+    /// Same seed gives the same seats.
+    pub fn simulate(&self, strategy: Strategy, passengers: usize, seed: u64) -> Vec<Seat> {
+        let layout = &self.layout;
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let mut seats: Vec<Seat> = (0..layout.seat_count())
+            .filter(|id| self.is_available(*id))
+            .map(|id| layout.seat_from_id(id).expect("IDs are on the plane"))
+            .collect();
+        let passengers = passengers.min(seats.len());
+
+        match strategy {
+            Strategy::Random => {
+                seats.shuffle(&mut rng);
+                seats.truncate(passengers);
+            }
+            Strategy::BackToFront => {
+                seats.shuffle(&mut rng);
+                seats.truncate(passengers);
+                // Stable sort keeps passengers of the same row in random order
+                seats.sort_by_key(|seat| layout.rows.size - seat.row());
+            }
+            Strategy::WindowMiddleAisle => {
+                seats.shuffle(&mut rng);
+                seats.truncate(passengers);
+                seats.sort_by_key(|seat| layout.columns.size - layout.aisle_distance(seat));
+            }
+            Strategy::Groups => seats = self.seat_groups(&mut rng, passengers),
+        }
+
+        seats
+    }
+
+    /// Fill random halves of rows with groups from the window to the aisle,
+    /// then let the groups board in random order.
+    ///
+    /// Seats that are not available split a half, groups never sit across them.
+    fn seat_groups(&self, rng: &mut StdRng, passengers: usize) -> Vec<Seat> {
+        let layout = &self.layout;
+        let mut sides: Vec<Vec<Seat>> = vec![];
+        for row in 0..layout.rows.size {
+            // Window seats go first on both sides
+            let (left, mut right): (Vec<Seat>, Vec<Seat>) = (0..layout.columns.size)
+                .map(|column| Seat::new(row, column))
+                .partition(|seat| layout.side(seat).1);
+            right.reverse();
+            for side in &[left, right] {
+                sides.extend(
+                    side.split(|seat| !self.is_seat_available(seat))
+                        .map(<[Seat]>::to_vec),
+                );
+            }
+        }
+        sides.retain(|side| !side.is_empty());
+        sides.shuffle(rng);
+
+        let mut groups: Vec<Vec<Seat>> = vec![];
+        let mut left = passengers;
+        for side in sides {
+            // Window first, so nobody climbs over the group
+            let mut side = side.into_iter().peekable();
+            while left > 0 && side.peek().is_some() {
+                let size = rng.gen_range(1..=MAX_GROUP.min(left));
+                let group: Vec<Seat> = side.by_ref().take(size).collect();
+                left -= group.len();
+                groups.push(group);
+            }
+        }
+        groups.shuffle(rng);
+
+        groups.into_iter().flatten().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use test_case::test_case;
+
+    fn layout() -> Layout {
+        "16 FB 8 LR".parse().unwrap()
+    }
+
+    fn plane() -> Plane {
+        Plane::new(layout())
+    }
+
+    #[test_case(Strategy::Random)]
+    #[test_case(Strategy::BackToFront)]
+    #[test_case(Strategy::WindowMiddleAisle)]
+    #[test_case(Strategy::Groups)]
+    fn every_passenger_gets_own_seat(strategy: Strategy) {
+        let (plane, layout) = (plane(), layout());
+
+        for passengers in &[0, 1, 50, 128, 500] {
+            let seats = plane.simulate(strategy, *passengers, 7);
+            let ids: HashSet<usize> = seats.iter().map(|seat| layout.seat_id(seat)).collect();
+
+            pretty_assertions::assert_eq!(seats.len(), (*passengers).min(128));
+            pretty_assertions::assert_eq!(ids.len(), seats.len());
+        }
+    }
+
+    #[test_case(Strategy::Random)]
+    #[test_case(Strategy::Groups)]
+    fn same_seed_same_seats(strategy: Strategy) {
+        let plane = plane();
+
+        pretty_assertions::assert_eq!(
+            plane.simulate(strategy, 100, 2020),
+            plane.simulate(strategy, 100, 2020)
+        );
+        assert_ne!(
+            plane.simulate(strategy, 100, 2020),
+            plane.simulate(strategy, 100, 2021)
+        );
+    }
+
+    #[test_case(Strategy::Random)]
+    #[test_case(Strategy::BackToFront)]
+    #[test_case(Strategy::WindowMiddleAisle)]
+    #[test_case(Strategy::Groups)]
+    fn only_available_seats_are_given_out(strategy: Strategy) {
+        let plane = Plane::parse(
+            layout(),
+            "nonexistent 13
+blocked 2 1-2
+blocked 7",
+        )
+        .unwrap();
+        let seats = plane.simulate(strategy, 500, 5);
+
+        pretty_assertions::assert_eq!(seats.len(), 128 - 8 - 2 - 8);
+        assert!(seats.iter().all(|seat| plane.is_seat_available(seat)));
+    }
+
+    #[test]
+    fn strategies_avoid_their_conflicts() {
+        let (plane, layout) = (plane(), layout());
+
+        let back_to_front = plane.simulate(Strategy::BackToFront, 128, 1);
+        pretty_assertions::assert_eq!(layout.conflicts(&back_to_front).aisle, 0);
+
+        let window_first = plane.simulate(Strategy::WindowMiddleAisle, 128, 1);
+        pretty_assertions::assert_eq!(layout.conflicts(&window_first).seat, 0);
+
+        let random = layout.conflicts(&plane.simulate(Strategy::Random, 128, 1));
+        assert!(random.aisle > 0 && random.seat > 0);
+    }
+
+    #[test]
+    fn group_members_sit_together() {
+        let layout = layout();
+        let seats = plane().simulate(Strategy::Groups, 128, 3);
+
+        // Every passenger sits next to the one before, or starts a new group
+        let neighbors = seats
+            .windows(2)
+            .filter(|pair| {
+                layout.side(&pair[0]) == layout.side(&pair[1])
+                    && layout.aisle_distance(&pair[0]) == layout.aisle_distance(&pair[1]) + 1
+            })
+            .count();
+        assert!(neighbors >= 128 / MAX_GROUP);
+    }
+
+    #[test]
+    fn conflicts() {
+        let layout = layout();
+        let seats = [
+            Seat::new(3, 3),
+            Seat::new(5, 2),
+            Seat::new(5, 0),
+            Seat::new(5, 4),
+            Seat::new(3, 0),
+        ];
+
+        pretty_assertions::assert_eq!(layout.conflicts(&seats), Conflicts { aisle: 1, seat: 2 });
+    }
+
+    #[test_case("groups" => Ok(Strategy::Groups))]
+    #[test_case("front-to-back" => Err("Strategy is not recognized: front-to-back".to_string()))]
+    fn strategy_from_str(s: &str) -> Result<Strategy, String> {
+        s.parse()
+    }
+}
@@ -1,14 +1,160 @@
-use std::{ops::Range, str::FromStr};
+use crate::plane::Plane;
+use std::{fmt, ops::Range, str::FromStr};
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub(crate) struct Seat {
     row: usize,
     column: usize,
 }
 
 impl Seat {
-    pub fn id(&self) -> usize {
-        self.row * 8 + self.column
+    pub fn new(row: usize, column: usize) -> Self {
+        Seat { row, column }
+    }
+
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl fmt::Display for Seat {
+    /// Boarding pass of the seat on the standard plane, e.g. "BFFFBBFRRR",
+    /// seats that the standard plane does not have are written as "row 200 column 3".
+    /// Other layouts go through `Layout::encode`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match Layout::default().encode(self) {
+            Ok(pass) => write!(f, "{}", pass),
+            Err(_) => write!(f, "row {} column {}", self.row, self.column),
+        }
+    }
+}
+
+/// One axis of the plane that a boarding pass narrows down, e.g. rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Dimension {
+    pub size: usize,
+    /// Letter that keeps the lower half
+    pub lower: char,
+    /// Letter that keeps the upper half
+    pub upper: char,
+}
+
+impl Dimension {
+    pub fn new(size: usize, lower: char, upper: char) -> Result<Self, String> {
+        if !size.is_power_of_two() {
+            return Err(format!("Dimension size {} is not a power of two", size));
+        }
+        if lower == upper {
+            return Err(format!("Dimension letters are the same: {}", lower));
+        }
+
+        Ok(Dimension { size, lower, upper })
+    }
+
+    /// Letters needed to pick one position, e.g. 7 for 128 rows
+    pub fn code_length(&self) -> usize {
+        self.size.trailing_zeros() as usize
+    }
+
+    fn has_letter(&self, letter: char) -> bool {
+        letter == self.lower || letter == self.upper
+    }
+}
+
+/// Sizes of the plane and letters of its boarding passes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Layout {
+    pub rows: Dimension,
+    pub columns: Dimension,
+}
+
+impl Default for Layout {
+    /// 128 rows by 8 columns, rows are picked by F/B and columns by L/R
+    fn default() -> Self {
+        Layout {
+            rows: Dimension {
+                size: 128,
+                lower: 'F',
+                upper: 'B',
+            },
+            columns: Dimension {
+                size: 8,
+                lower: 'L',
+                upper: 'R',
+            },
+        }
+    }
+}
+
+impl Layout {
+    pub fn new(rows: Dimension, columns: Dimension) -> Result<Self, String> {
+        if rows.has_letter(columns.lower) || rows.has_letter(columns.upper) {
+            return Err(format!(
+                "Row letters {}{} and column letters {}{} overlap",
+                rows.lower, rows.upper, columns.lower, columns.upper
+            ));
+        }
+
+        Ok(Layout { rows, columns })
+    }
+
+    /// Row letters go first, then column letters
+    pub fn code_length(&self) -> usize {
+        self.rows.code_length() + self.columns.code_length()
+    }
+
+    /// Seats are numbered row by row
+    pub fn seat_id(&self, seat: &Seat) -> usize {
+        seat.row * self.columns.size + seat.column
+    }
+
+    /// Narrow the seat range letter by letter, see `check_letters` for bad passes
+    pub fn parse_seat(&self, s: &str) -> Result<Seat, String> {
+        if let Some(issue) = self.check_letters(s) {
+            return Err(format!("Boarding pass {} {}", s, issue));
+        }
+
+        let seat_range = s
+            .chars()
+            .fold(SeatRange::new(self), |seat_range, movement| {
+                seat_range
+                    .calculate_next(self, movement)
+                    .expect("Letters are checked")
+            });
+
+        Ok(Seat {
+            row: seat_range.rows.start,
+            column: seat_range.columns.start,
+        })
+    }
+}
+
+impl FromStr for Layout {
+    type Err = String;
+
+    /// Example: "256 FB 16 LR" -> 256 rows picked by F/B, 16 columns picked by L/R
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let dimension = |size: &str, letters: &str| {
+            let size = size
+                .parse()
+                .map_err(|_| format!("Dimension size is not a number: {}", size))?;
+            match letters.chars().collect::<Vec<char>>()[..] {
+                [lower, upper] => Dimension::new(size, lower, upper),
+                _ => Err(format!("Dimension needs two letters, got {}", letters)),
+            }
+        };
+
+        match s.split_whitespace().collect::<Vec<&str>>()[..] {
+            [rows, row_letters, columns, column_letters] => Layout::new(
+                dimension(rows, row_letters)?,
+                dimension(columns, column_letters)?,
+            ),
+            _ => Err(format!("Can not parse layout {}", s)),
+        }
     }
 }
 
@@ -18,35 +164,40 @@ struct SeatRange {
     columns: Range<usize>,
 }
 
-impl Default for SeatRange {
-    fn default() -> Self {
+impl SeatRange {
+    fn new(layout: &Layout) -> Self {
         SeatRange {
-            rows: 0..128,
-            columns: 0..8,
+            rows: 0..layout.rows.size,
+            columns: 0..layout.columns.size,
         }
     }
-}
 
-impl SeatRange {
-    fn calculate_next(&self, movement: &char) -> Self {
-        match movement {
-            'B' => SeatRange {
-                rows: ((self.rows.end + self.rows.start) / 2)..(self.rows.end),
+    /// Letters that are not in the layout give nothing
+    fn calculate_next(&self, layout: &Layout, movement: char) -> Option<Self> {
+        let middle = |range: &Range<usize>| (range.start + range.end) / 2;
+
+        if movement == layout.rows.upper {
+            Some(SeatRange {
+                rows: middle(&self.rows)..(self.rows.end),
                 columns: self.columns.clone(),
-            },
-            'F' => SeatRange {
-                rows: (self.rows.start)..((self.rows.end + self.rows.start) / 2),
+            })
+        } else if movement == layout.rows.lower {
+            Some(SeatRange {
+                rows: (self.rows.start)..middle(&self.rows),
                 columns: self.columns.clone(),
-            },
-            'L' => SeatRange {
+            })
+        } else if movement == layout.columns.lower {
+            Some(SeatRange {
                 rows: self.rows.clone(),
-                columns: (self.columns.start)..((self.columns.end + self.columns.start) / 2),
-            },
-            'R' => SeatRange {
+                columns: (self.columns.start)..middle(&self.columns),
+            })
+        } else if movement == layout.columns.upper {
+            Some(SeatRange {
                 rows: self.rows.clone(),
-                columns: ((self.columns.start + self.columns.end) / 2)..(self.columns.end),
-            },
-            _ => unreachable!(),
+                columns: middle(&self.columns)..(self.columns.end),
+            })
+        } else {
+            None
         }
     }
 }
@@ -54,29 +205,10 @@ impl SeatRange {
 impl FromStr for Seat {
     type Err = String;
 
+    /// Boarding pass of the standard plane, see `Plane::standard`,
+    /// passes to seats the plane does not have are bad
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let seat_range = s.chars().enumerate().try_fold(
-            SeatRange::default(),
-            |seat_range, (index, movement)| {
-                if !matches!(movement, 'B' | 'F' | 'L' | 'R') {
-                    Err(format!("Bad movement {} at index {}", movement, index))
-                } else {
-                    Ok(seat_range.calculate_next(&movement))
-                }
-            },
-        )?;
-
-        if !(seat_range.rows.len() == 1 && seat_range.columns.len() == 1) {
-            return Err(format!(
-                "Read all movements but range is not empty: {:?}",
-                seat_range,
-            ));
-        }
-
-        Ok(Seat {
-            row: seat_range.rows.start,
-            column: seat_range.columns.start,
-        })
+        Plane::standard().parse_seat(s)
     }
 }
 
@@ -98,6 +230,41 @@ mod tests {
     #[test_case(Seat {row: 102, column: 4} => 820)]
     #[test_case(Seat {row: 44,  column: 5} => 357)]
     fn id_of_seat(seat: Seat) -> usize {
-        seat.id()
+        Layout::default().seat_id(&seat)
+    }
+
+    #[test_case("256 FB 16 LR" => Ok((8, 4)))]
+    #[test_case("1 FB 2 LR" => Ok((0, 1)))]
+    #[test_case("128 FB 8 LR" => Ok((7, 3)))]
+    #[test_case("100 FB 8 LR" => Err("Dimension size 100 is not a power of two".to_string()))]
+    #[test_case("0 FB 8 LR" => Err("Dimension size 0 is not a power of two".to_string()))]
+    #[test_case("128 FF 8 LR" => Err("Dimension letters are the same: F".to_string()))]
+    #[test_case("128 FB 8 BR" => Err("Row letters FB and column letters BR overlap".to_string()))]
+    #[test_case("128 FBL 8 LR" => Err("Dimension needs two letters, got FBL".to_string()))]
+    #[test_case("128 FB" => Err("Can not parse layout 128 FB".to_string()))]
+    fn code_lengths_of_layout(layout: &str) -> Result<(usize, usize), String> {
+        let layout: Layout = layout.parse()?;
+        Ok((layout.rows.code_length(), layout.columns.code_length()))
+    }
+
+    #[test_case("UDDUUDDULLHL", Ok(Seat {row: 102, column: 2}) ; "bigger plane")]
+    #[test_case("UDDUUDDURLHL", Err("Boarding pass UDDUUDDURLHL has bad letter R at index 8".to_string()) ; "letter of other layout")]
+    #[test_case("UDDUUDDLDLHL", Err("Boarding pass UDDUUDDLDLHL has column letter L at index 7 in the row part".to_string()) ; "letter of other part")]
+    #[test_case("BFFFBBFRRR", Err("Boarding pass BFFFBBFRRR has 10 letters, expected 12".to_string()) ; "pass of other plane")]
+    fn seat_from_str_with_layout(x: &str, seat: Result<Seat, String>) {
+        let layout: Layout = "256 UD 16 LH".parse().unwrap();
+        pretty_assertions::assert_eq!(layout.parse_seat(x), seat)
+    }
+
+    #[test]
+    fn id_of_seat_with_layout() {
+        let layout: Layout = "256 FB 16 LR".parse().unwrap();
+        let seat = Seat {
+            row: 102,
+            column: 2,
+        };
+
+        assert_eq!(layout.code_length(), 12);
+        assert_eq!(layout.seat_id(&seat), 102 * 16 + 2);
     }
 }
@@ -0,0 +1,237 @@
+use crate::plane::{Plane, SeatKind};
+use crate::types::{Layout, Seat};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+};
+
+/// Part of a boarding pass, row letters go first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Part {
+    Row,
+    Column,
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Part::Row => write!(f, "row"),
+            Part::Column => write!(f, "column"),
+        }
+    }
+}
+
+/// What is wrong with a single boarding pass
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PassIssue {
+    WrongLength {
+        length: usize,
+        expected: usize,
+    },
+    /// Letter that the layout does not use at all
+    BadLetter {
+        letter: char,
+        index: usize,
+    },
+    /// Letter of the other part, e.g. "L" among row letters
+    WrongPart {
+        letter: char,
+        index: usize,
+        part: Part,
+    },
+    /// Pass decodes to a seat that the plane does not have
+    Nonexistent {
+        seat: Seat,
+    },
+    /// Pass decodes to the seat of an earlier pass
+    Duplicate {
+        seat: Seat,
+        first_line: usize,
+    },
+}
+
+/// Issue of the pass at `line`, lines start from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Issue {
+    pub line: usize,
+    pub pass: String,
+    pub kind: PassIssue,
+}
+
+impl fmt::Display for PassIssue {
+    /// Example: "has column letter L at index 2 in the row part", the pass itself goes in front
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PassIssue::WrongLength { length, expected } => {
+                write!(f, "has {} letters, expected {}", length, expected)
+            }
+            PassIssue::BadLetter { letter, index } => {
+                write!(f, "has bad letter {} at index {}", letter, index)
+            }
+            PassIssue::WrongPart {
+                letter,
+                index,
+                part,
+            } => {
+                let other = match part {
+                    Part::Row => Part::Column,
+                    Part::Column => Part::Row,
+                };
+                write!(
+                    f,
+                    "has {} letter {} at index {} in the {} part",
+                    other, letter, index, part
+                )
+            }
+            PassIssue::Nonexistent { seat } => write!(
+                f,
+                "is row {} column {}, the plane does not have it",
+                seat.row(),
+                seat.column()
+            ),
+            PassIssue::Duplicate { seat, first_line } => write!(
+                f,
+                "is row {} column {}, same seat as on line {}",
+                seat.row(),
+                seat.column(),
+                first_line
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    /// Example: "Line 5: FBLFBBFRLR has column letter L at index 2 in the row part"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: {} {}", self.line, self.pass, self.kind)
+    }
+}
+
+/// Passes that are fine and issues of the rest
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Validated {
+    pub seats: Vec<Seat>,
+    pub issues: Vec<Issue>,
+}
+
+impl Layout {
+    /// First thing that is wrong with the letters of the pass,
+    /// every decoder rejects passes with such an issue
+    pub fn check_letters(&self, pass: &str) -> Option<PassIssue> {
+        let length = pass.chars().count();
+        if length != self.code_length() {
+            return Some(PassIssue::WrongLength {
+                length,
+                expected: self.code_length(),
+            });
+        }
+
+        pass.chars().enumerate().find_map(|(index, letter)| {
+            let (part, own, other) = if index < self.rows.code_length() {
+                (Part::Row, self.rows, self.columns)
+            } else {
+                (Part::Column, self.columns, self.rows)
+            };
+
+            if letter == own.lower || letter == own.upper {
+                None
+            } else if letter == other.lower || letter == other.upper {
+                Some(PassIssue::WrongPart {
+                    letter,
+                    index,
+                    part,
+                })
+            } else {
+                Some(PassIssue::BadLetter { letter, index })
+            }
+        })
+    }
+}
+
+impl Plane {
+    /// Check one boarding pass per line, blank lines are skipped.
+    ///
+    /// The first pass of a seat is kept, later passes to the same seat are duplicates.
+    /// Passes to seats the plane does not have are never kept.
+    pub fn validate(&self, text: &str) -> Validated {
+        let mut validated = Validated::default();
+        // Line of the first pass of every seat ID
+        let mut first_lines = HashMap::new();
+
+        for (index, pass) in text.lines().enumerate() {
+            let (line, pass) = (index + 1, pass.trim());
+            if pass.is_empty() {
+                continue;
+            }
+
+            let kind = match self.layout.check_letters(pass) {
+                Some(kind) => kind,
+                None => {
+                    let seat = self.layout.decode(pass).expect("Letters are checked");
+                    match first_lines.entry(self.layout.seat_id(&seat)) {
+                        _ if self.kind(&seat) == SeatKind::Nonexistent => {
+                            PassIssue::Nonexistent { seat }
+                        }
+                        Entry::Vacant(entry) => {
+                            entry.insert(line);
+                            validated.seats.push(seat);
+                            continue;
+                        }
+                        Entry::Occupied(entry) => PassIssue::Duplicate {
+                            seat,
+                            first_line: *entry.get(),
+                        },
+                    }
+                }
+            };
+
+            validated.issues.push(Issue {
+                line,
+                pass: pass.to_string(),
+                kind,
+            });
+        }
+
+        validated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    #[test]
+    fn validate() {
+        let text = "BFFFBBFRRR\nFFFBBBFRRR\n\nBFFFBBFRR\nFBLFBBFRLR\nBFFFBBFRRX\nBFFFBBFRRR\nFBFBBFFRBR\nBFFFBBFRRR\nFFFBBFBLLL\n";
+        let plane = Plane::parse(Layout::default(), "nonexistent 13").unwrap();
+        let validated = plane.validate(text);
+
+        pretty_assertions::assert_eq!(validated.seats, vec![Seat::new(70, 7), Seat::new(14, 7)]);
+        pretty_assertions::assert_eq!(
+            validated
+                .issues
+                .iter()
+                .map(Issue::to_string)
+                .collect::<Vec<String>>(),
+            vec![
+                "Line 4: BFFFBBFRR has 9 letters, expected 10",
+                "Line 5: FBLFBBFRLR has column letter L at index 2 in the row part",
+                "Line 6: BFFFBBFRRX has bad letter X at index 9",
+                "Line 7: BFFFBBFRRR is row 70 column 7, same seat as on line 1",
+                "Line 8: FBFBBFFRBR has row letter B at index 8 in the column part",
+                "Line 9: BFFFBBFRRR is row 70 column 7, same seat as on line 1",
+                "Line 10: FFFBBFBLLL is row 13 column 0, the plane does not have it",
+            ]
+        );
+    }
+
+    #[test_case("BFFFBBFRRR" => None)]
+    #[test_case("BFFFBBFRRRR" => Some(PassIssue::WrongLength { length: 11, expected: 10 }))]
+    #[test_case("LFFFBBFRRR" => Some(PassIssue::WrongPart { letter: 'L', index: 0, part: Part::Row }))]
+    #[test_case("BFFFBBFRRF" => Some(PassIssue::WrongPart { letter: 'F', index: 9, part: Part::Column }))]
+    #[test_case("bFFFBBFRRR" => Some(PassIssue::BadLetter { letter: 'b', index: 0 }))]
+    fn check_letters(pass: &str) -> Option<PassIssue> {
+        Layout::default().check_letters(pass)
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashSet;
+
+/// Questions a-z as bits of a number, bit 0 is question "a"
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AnswerBits(u32);
+
+impl AnswerBits {
+    /// Nothing when one of the characters is not a question a-z
+    pub fn from_chars(chars: impl IntoIterator<Item = char>) -> Option<Self> {
+        chars.into_iter().try_fold(AnswerBits(0), |bits, question| {
+            if question.is_ascii_lowercase() {
+                Some(AnswerBits(bits.0 | 1 << (question as u8 - b'a')))
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn union(self, other: AnswerBits) -> AnswerBits {
+        AnswerBits(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: AnswerBits) -> AnswerBits {
+        AnswerBits(self.0 & other.0)
+    }
+
+    /// Questions of `self` that are not in `other`
+    pub fn difference(self, other: AnswerBits) -> AnswerBits {
+        AnswerBits(self.0 & !other.0)
+    }
+
+    pub fn count(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn contains(self, question: char) -> bool {
+        question.is_ascii_lowercase() && self.0 >> (question as u8 - b'a') & 1 == 1
+    }
+
+    /// Questions in alphabetical order
+    pub fn chars(self) -> impl Iterator<Item = char> {
+        (b'a'..=b'z')
+            .filter(move |question| self.0 >> (question - b'a') & 1 == 1)
+            .map(char::from)
+    }
+}
+
+/// Questions that got a "yes", as bits when all of them are a-z, as a set otherwise
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Answers {
+    Bits(AnswerBits),
+    Set(HashSet<char>),
+}
+
+impl Default for Answers {
+    fn default() -> Self {
+        Answers::Bits(AnswerBits::default())
+    }
+}
+
+impl Answers {
+    pub fn new(questions: &str) -> Self {
+        match AnswerBits::from_chars(questions.chars()) {
+            Some(bits) => Answers::Bits(bits),
+            None => Answers::Set(questions.chars().collect()),
+        }
+    }
+
+    pub fn to_set(&self) -> HashSet<char> {
+        match self {
+            Answers::Bits(bits) => bits.chars().collect(),
+            Answers::Set(set) => set.clone(),
+        }
+    }
+
+    /// Bits if both are bits, sets are combined otherwise
+    fn combine(
+        &self,
+        other: &Answers,
+        bits: fn(AnswerBits, AnswerBits) -> AnswerBits,
+        set: fn(&HashSet<char>, &HashSet<char>) -> HashSet<char>,
+    ) -> Answers {
+        match (self, other) {
+            (Answers::Bits(first), Answers::Bits(second)) => Answers::Bits(bits(*first, *second)),
+            _ => Answers::Set(set(&self.to_set(), &other.to_set())),
+        }
+    }
+
+    pub fn union(&self, other: &Answers) -> Answers {
+        self.combine(other, AnswerBits::union, |first, second| {
+            first.union(second).copied().collect()
+        })
+    }
+
+    pub fn intersection(&self, other: &Answers) -> Answers {
+        self.combine(other, AnswerBits::intersection, |first, second| {
+            first.intersection(second).copied().collect()
+        })
+    }
+
+    pub fn difference(&self, other: &Answers) -> Answers {
+        self.combine(other, AnswerBits::difference, |first, second| {
+            first.difference(second).copied().collect()
+        })
+    }
+
+    pub fn contains(&self, question: char) -> bool {
+        match self {
+            Answers::Bits(bits) => bits.contains(question),
+            Answers::Set(set) => set.contains(&question),
+        }
+    }
+
+    /// Questions in alphabetical order
+    pub fn questions(&self) -> Vec<char> {
+        match self {
+            Answers::Bits(bits) => bits.chars().collect(),
+            Answers::Set(set) => {
+                let mut questions: Vec<char> = set.iter().copied().collect();
+                questions.sort_unstable();
+                questions
+            }
+        }
+    }
+
+    /// Number of questions
+    pub fn count(&self) -> usize {
+        match self {
+            Answers::Bits(bits) => bits.count(),
+            Answers::Set(set) => set.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn set(questions: &str) -> HashSet<char> {
+        questions.chars().collect()
+    }
+
+    #[test]
+    fn answer_bits() {
+        let first = AnswerBits::from_chars("abcz".chars()).unwrap();
+        let second = AnswerBits::from_chars("bcdb".chars()).unwrap();
+
+        assert_eq!(first.count(), 4);
+        assert_eq!(first.union(second).chars().collect::<String>(), "abcdz");
+        assert_eq!(first.intersection(second).chars().collect::<String>(), "bc");
+        assert_eq!(first.difference(second).chars().collect::<String>(), "az");
+        assert!(first.contains('z') && !first.contains('d') && !first.contains('é'));
+        assert_eq!(AnswerBits::from_chars("".chars()), Some(AnswerBits(0)));
+        assert_eq!(AnswerBits::from_chars("abC".chars()), None);
+    }
+
+    #[test]
+    fn answers_fall_back_to_set() {
+        let bits = Answers::new("abc");
+        let other = Answers::new("bcé");
+
+        assert!(matches!(bits, Answers::Bits(_)));
+        assert_eq!(other, Answers::Set(set("bcé")));
+        assert_eq!(bits.union(&other), Answers::Set(set("abcé")));
+        assert_eq!(bits.intersection(&other), Answers::Set(set("bc")));
+        assert_eq!(other.difference(&bits), Answers::Set(set("é")));
+        assert_eq!(other.count(), 3);
+        assert_eq!(other.questions(), vec!['b', 'c', 'é']);
+        assert!(other.contains('é') && !bits.contains('é'));
+    }
+
+    #[test]
+    fn bits_and_sets_agree() {
+        let groups = ["abc", "xyz", "", "qwertyuiopasdfghjklzxcvbnm", "aaab"];
+
+        for first in &groups {
+            for second in &groups {
+                let (bits, other) = (Answers::new(first), Answers::new(second));
+                let (set, other_set) = (set(first), set(second));
+
+                assert_eq!(bits.union(&other).to_set(), &set | &other_set);
+                assert_eq!(bits.intersection(&other).to_set(), &set & &other_set);
+                assert_eq!(bits.difference(&other).to_set(), &set - &other_set);
+                assert_eq!(bits.count(), set.len());
+            }
+        }
+    }
+}
@@ -1,36 +1,77 @@
+use crate::answers::Answers;
+use crate::quorum::Quorum;
+use crate::stats::{Format, Report, TOP_PAIRS};
+use crate::types::{parse_groups, Group};
 use std::collections::HashSet;
-use std::fs::read_to_string;
+use std::env;
+use std::fs::{self, read_to_string};
+use std::process;
+use std::time::Instant;
 
-fn read_input(filename: &str) -> Vec<String> {
-    read_to_string(filename)
-        .unwrap_or_else(|_| panic!("No file named {} found", filename))
-        .split("\n\n")
-        .map(str::to_owned)
+mod answers;
+mod quorum;
+mod stats;
+mod types;
+
+const INPUT_FILENAME: &str = "input.txt";
+
+/// Input repeats of the benchmark, when not given
+const BENCH_REPEAT: usize = 1000;
+
+const USAGE: &str = "Usage: day6 [bench [repeat] | partial \
+                     | quorum <anyone|everyone|at-least:K|at-least:P%|exactly:K> \
+                     | stats [json|csv] [output file] | --group <number>]";
+
+fn read_input(filename: &str) -> Result<Vec<Group>, String> {
+    let text =
+        read_to_string(filename).map_err(|err| format!("Can not read {}: {}", filename, err))?;
+
+    parse_groups(&text)
+}
+
+/// Questions of every group that enough of its members answered
+fn find_answered_by(groups: &[Group], quorum: Quorum) -> Vec<Answers> {
+    groups
+        .iter()
+        .map(|group| quorum.answered(&group.answers()))
         .collect()
 }
 
-fn find_what_anyone_answered(answers: &[String]) -> Vec<HashSet<char>> {
-    answers
+fn find_what_anyone_answered(groups: &[Group]) -> Vec<Answers> {
+    find_answered_by(groups, Quorum::ANYONE)
+}
+
+/// Nothing for a group without members
+fn find_what_everyone_answered(groups: &[Group]) -> Vec<Answers> {
+    find_answered_by(groups, Quorum::EVERYONE)
+}
+
+/// Reference for `find_what_anyone_answered`, used by the benchmark
+fn find_what_anyone_answered_with_sets(groups: &[Group]) -> Vec<HashSet<char>> {
+    groups
         .iter()
-        .map(|group_answers| {
-            group_answers
-                .split_whitespace()
-                .flat_map(|person_answers| person_answers.chars())
+        .map(|group| {
+            group
+                .members
+                .iter()
+                .flat_map(|person| person.text().chars())
                 .collect()
         })
         .collect()
 }
 
-fn find_what_everyone_answered(answers: &[String]) -> Vec<HashSet<char>> {
-    answers
+/// Reference for `find_what_everyone_answered`, used by the benchmark
+fn find_what_everyone_answered_with_sets(groups: &[Group]) -> Vec<HashSet<char>> {
+    groups
         .iter()
-        .map(|group_answers| {
-            let mut group_answers: Vec<HashSet<char>> = group_answers
-                .split_whitespace()
-                .map(|person_answers| person_answers.chars().collect::<HashSet<char>>())
+        .map(|group| {
+            let mut group_answers: Vec<HashSet<char>> = group
+                .members
+                .iter()
+                .map(|person| person.text().chars().collect::<HashSet<char>>())
                 .collect();
 
-            let mut result = group_answers.pop().unwrap();
+            let mut result = group_answers.pop().unwrap_or_default();
             result.retain(|item| {
                 group_answers
                     .iter()
@@ -41,22 +82,217 @@ fn find_what_everyone_answered(answers: &[String]) -> Vec<HashSet<char>> {
         .collect()
 }
 
-fn main() {
-    let answers = read_input("input.txt");
+fn solve(groups: &[Group]) {
+    let answers_anyone = find_what_anyone_answered(groups);
 
-    let answers_anyone = find_what_anyone_answered(&answers);
+    let total_answers_anyone: usize = answers_anyone.iter().map(Answers::count).sum();
+    println!("Total anyone answer count {}", &total_answers_anyone);
+
+    let answers_everyone = find_what_everyone_answered(groups);
+
+    let total_answers_everyone: usize = answers_everyone.iter().map(Answers::count).sum();
+    println!("Total everyone answer count {}", &total_answers_everyone);
+}
 
-    let total_answers_anyone: usize = answers_anyone
+/// Questions somebody in the group answered, but not everyone
+fn count_partial(groups: &[Group]) {
+    let total: usize = find_what_anyone_answered(groups)
         .iter()
-        .map(|group_answers| group_answers.len())
+        .zip(&find_what_everyone_answered(groups))
+        .map(|(anyone, everyone)| anyone.difference(everyone).count())
         .sum();
-    println!("Total anyone answer count {}", &total_answers_anyone);
-
-    let answers_everyone = find_what_everyone_answered(&answers);
+    println!("Total answers not everyone gave {}", total);
+}
 
-    let total_answers_everyone: usize = answers_everyone
+fn count_quorum(groups: &[Group], quorum: Quorum) {
+    let total: usize = find_answered_by(groups, quorum)
         .iter()
-        .map(|group_answers| group_answers.len())
+        .map(Answers::count)
         .sum();
-    println!("Total everyone answer count {}", &total_answers_everyone);
+    println!("Total {} answer count {}", quorum, total);
+}
+
+/// Statistics of every question to the terminal or to a file
+fn write_stats(groups: &[Group], format: Format, output: Option<&str>) -> Result<(), String> {
+    let report = Report::new(groups, TOP_PAIRS);
+    let text = match format {
+        Format::Json => report.to_json(),
+        Format::Csv => report.to_csv(),
+    };
+
+    match output {
+        Some(filename) => {
+            fs::write(filename, text).map_err(|err| format!("Can not write {}: {}", filename, err))
+        }
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+/// Who answered every question of the group, groups are numbered from 1 like in errors
+fn explain_group(groups: &[Group], number: usize) -> Result<(), String> {
+    let group = number
+        .checked_sub(1)
+        .and_then(|index| groups.get(index))
+        .ok_or_else(|| {
+            format!(
+                "Group {} does not exist, groups go up to {}",
+                number,
+                groups.len()
+            )
+        })?;
+    // Members are on consecutive lines
+    let person = |index: &usize| {
+        format!(
+            "{} (line {})",
+            group.members[*index].text(),
+            group.line + index
+        )
+    };
+    let people = |indexes: &[usize]| {
+        indexes
+            .iter()
+            .map(person)
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+
+    println!(
+        "Group {} on line {}, {} people",
+        number,
+        group.line,
+        group.members.len()
+    );
+    for members in group.question_members() {
+        if members.is_partial() {
+            println!(
+                "{}: answered by {}; missing from {}",
+                members.question,
+                people(&members.answered),
+                people(&members.missing)
+            );
+        } else {
+            println!("{}: answered by everyone", members.question);
+        }
+    }
+
+    Ok(())
+}
+
+/// Solve both parts for the input repeated `repeat` times with bitmasks and with hash sets
+fn bench(groups: &[Group], repeat: usize) {
+    let groups: Vec<Group> = groups
+        .iter()
+        .cycle()
+        .take(groups.len() * repeat)
+        .cloned()
+        .collect();
+
+    let solvers: &[(&str, &dyn Fn() -> usize)] = &[
+        ("bitmasks", &|| {
+            find_what_anyone_answered(&groups)
+                .iter()
+                .chain(&find_what_everyone_answered(&groups))
+                .map(Answers::count)
+                .sum()
+        }),
+        ("hash sets", &|| {
+            find_what_anyone_answered_with_sets(&groups)
+                .iter()
+                .chain(&find_what_everyone_answered_with_sets(&groups))
+                .map(HashSet::len)
+                .sum()
+        }),
+    ];
+
+    for (name, solve) in solvers {
+        let start = Instant::now();
+        let total = solve();
+        let seconds = start.elapsed().as_secs_f64();
+
+        println!(
+            "{:<9}: {} groups in {:.3}s, {:.0} groups/s, total {}",
+            name,
+            groups.len(),
+            seconds,
+            groups.len() as f64 / seconds,
+            total
+        );
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let groups = read_input(INPUT_FILENAME)?;
+
+    match args.iter().map(String::as_str).collect::<Vec<&str>>()[..] {
+        [] => solve(&groups),
+        ["bench"] => bench(&groups, BENCH_REPEAT),
+        ["bench", repeat] => bench(&groups, repeat.parse().map_err(|_| USAGE)?),
+        ["partial"] => count_partial(&groups),
+        ["quorum", quorum] => count_quorum(&groups, quorum.parse()?),
+        ["stats"] => write_stats(&groups, Format::Json, None)?,
+        ["stats", format] => write_stats(&groups, format.parse()?, None)?,
+        ["stats", format, output] => write_stats(&groups, format.parse()?, Some(output))?,
+        ["--group", number] => explain_group(&groups, number.parse().map_err(|_| USAGE)?)?,
+        _ => return Err(USAGE.into()),
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if let Err(err) = run(&args) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn bitmasks_match_hash_sets() {
+        let groups =
+            parse_groups("abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb\n\nxé\nxy\n").unwrap();
+
+        let anyone: Vec<HashSet<char>> = find_what_anyone_answered(&groups)
+            .iter()
+            .map(Answers::to_set)
+            .collect();
+        let everyone: Vec<HashSet<char>> = find_what_everyone_answered(&groups)
+            .iter()
+            .map(Answers::to_set)
+            .collect();
+
+        assert_eq!(anyone, find_what_anyone_answered_with_sets(&groups));
+        assert_eq!(everyone, find_what_everyone_answered_with_sets(&groups));
+        assert_eq!(
+            anyone.iter().map(HashSet::len).collect::<Vec<usize>>(),
+            vec![3, 3, 3, 1, 1, 3]
+        );
+        assert_eq!(
+            everyone.iter().map(HashSet::len).collect::<Vec<usize>>(),
+            vec![3, 0, 1, 1, 1, 1]
+        );
+    }
+
+    #[test]
+    fn empty_group_answered_nothing() {
+        let groups = [Group::default()];
+
+        assert_eq!(
+            find_what_everyone_answered(&groups),
+            vec![Answers::default()]
+        );
+        assert_eq!(
+            find_what_everyone_answered_with_sets(&groups),
+            vec![HashSet::new()]
+        );
+    }
 }
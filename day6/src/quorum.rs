@@ -0,0 +1,222 @@
+use crate::answers::Answers;
+use std::{fmt, str::FromStr};
+
+/// How many members of a group have to answer a question "yes" for it to count
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Quorum {
+    /// At least this many members
+    AtLeast(usize),
+    /// At least this percent of the members, rounded up
+    AtLeastPercent(usize),
+    /// Exactly this many members
+    Exactly(usize),
+}
+
+impl Quorum {
+    /// Somebody in the group answered, the union of member answers
+    pub const ANYONE: Quorum = Quorum::AtLeast(1);
+    /// Every member answered, the intersection of member answers
+    pub const EVERYONE: Quorum = Quorum::AtLeastPercent(100);
+
+    /// Question that `count` of `members` answered meets the quorum
+    pub fn is_met(&self, count: usize, members: usize) -> bool {
+        match *self {
+            Quorum::AtLeast(needed) => count >= needed,
+            Quorum::AtLeastPercent(percent) => count > 0 && count * 100 >= percent * members,
+            Quorum::Exactly(needed) => count == needed,
+        }
+    }
+
+    /// Questions of one group that meet the quorum, `members` are answers of every member
+    pub fn answered(&self, members: &[&Answers]) -> Answers {
+        match *self {
+            Quorum::ANYONE => members
+                .iter()
+                .fold(Answers::default(), |anyone, member| anyone.union(member)),
+            Quorum::EVERYONE => match members.split_first() {
+                Some((first, rest)) => rest.iter().fold((*first).clone(), |everyone, member| {
+                    everyone.intersection(member)
+                }),
+                None => Answers::default(),
+            },
+            _ => self.count_answered(members),
+        }
+    }
+
+    /// Count members of every question anyone answered, works for every quorum
+    fn count_answered(&self, members: &[&Answers]) -> Answers {
+        let questions: String = Quorum::ANYONE
+            .answered(members)
+            .questions()
+            .into_iter()
+            .filter(|question| {
+                let count = members
+                    .iter()
+                    .filter(|member| member.contains(*question))
+                    .count();
+                self.is_met(count, members.len())
+            })
+            .collect();
+
+        Answers::new(&questions)
+    }
+}
+
+impl FromStr for Quorum {
+    type Err = String;
+
+    /// Example: "anyone", "everyone", "at-least:2", "at-least:50%", "exactly:1"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |n: &str| {
+            n.parse::<usize>()
+                .map_err(|_| format!("Quorum size is not a number: {}", n))
+        };
+        // Questions nobody answered are never counted, so no member is never enough
+        let size = |n: &str| match number(n)? {
+            0 => Err("Quorum size 0 is never met".to_string()),
+            needed => Ok(needed),
+        };
+
+        match s.split_once(':') {
+            None if s == "anyone" => Ok(Quorum::ANYONE),
+            None if s == "everyone" => Ok(Quorum::EVERYONE),
+            Some(("at-least", percent)) if percent.ends_with('%') => {
+                match number(percent.trim_end_matches('%'))? {
+                    percent if percent <= 100 => Ok(Quorum::AtLeastPercent(percent)),
+                    percent => Err(format!("Quorum percent {} is over 100", percent)),
+                }
+            }
+            Some(("at-least", needed)) => Ok(Quorum::AtLeast(size(needed)?)),
+            Some(("exactly", needed)) => Ok(Quorum::Exactly(size(needed)?)),
+            _ => Err(format!("Quorum is not recognized: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Quorum {
+    /// Same text the quorum is parsed from
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Quorum::ANYONE => write!(f, "anyone"),
+            Quorum::EVERYONE => write!(f, "everyone"),
+            Quorum::AtLeast(needed) => write!(f, "at-least:{}", needed),
+            Quorum::AtLeastPercent(percent) => write!(f, "at-least:{}%", percent),
+            Quorum::Exactly(needed) => write!(f, "exactly:{}", needed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Example groups of the puzzle
+    const EXAMPLE: [&[&str]; 5] = [
+        &["abc"],
+        &["a", "b", "c"],
+        &["ab", "ac"],
+        &["a", "a", "a", "a"],
+        &["b"],
+    ];
+
+    fn answered(quorum: &str) -> Vec<String> {
+        let quorum: Quorum = quorum.parse().unwrap();
+
+        EXAMPLE
+            .iter()
+            .map(|group| {
+                let members: Vec<Answers> =
+                    group.iter().map(|member| Answers::new(member)).collect();
+                let members: Vec<&Answers> = members.iter().collect();
+                quorum.answered(&members).questions().into_iter().collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn quorums_of_example_groups() {
+        let cases: &[(&str, [&str; 5])] = &[
+            ("anyone", ["abc", "abc", "abc", "a", "b"]),
+            ("everyone", ["abc", "", "a", "a", "b"]),
+            ("at-least:1", ["abc", "abc", "abc", "a", "b"]),
+            ("at-least:2", ["", "", "a", "a", ""]),
+            ("at-least:100%", ["abc", "", "a", "a", "b"]),
+            ("at-least:50%", ["abc", "", "abc", "a", "b"]),
+            ("at-least:0%", ["abc", "abc", "abc", "a", "b"]),
+            ("exactly:1", ["abc", "abc", "bc", "", "b"]),
+            ("exactly:4", ["", "", "", "a", ""]),
+        ];
+
+        for (quorum, expected) in cases {
+            assert_eq!(answered(quorum), expected.to_vec(), "{}", quorum);
+        }
+    }
+
+    #[test]
+    fn special_cases_match_counting() {
+        let quorums = [Quorum::ANYONE, Quorum::EVERYONE];
+
+        for quorum in &quorums {
+            for group in &EXAMPLE {
+                let members: Vec<Answers> =
+                    group.iter().map(|member| Answers::new(member)).collect();
+                let members: Vec<&Answers> = members.iter().collect();
+                assert_eq!(quorum.answered(&members), quorum.count_answered(&members));
+            }
+        }
+    }
+
+    #[test]
+    fn quorums_of_questions_that_are_not_a_z() {
+        let (first, second) = (Answers::new("xé"), Answers::new("xy"));
+        let members = [&first, &second];
+
+        assert_eq!(Quorum::ANYONE.answered(&members), Answers::new("éxy"));
+        assert_eq!(
+            Quorum::EVERYONE.answered(&members).to_set(),
+            Answers::new("x").to_set()
+        );
+        assert_eq!(
+            Quorum::Exactly(1).answered(&members).to_set(),
+            Answers::new("éy").to_set()
+        );
+        for quorum in &[Quorum::ANYONE, Quorum::EVERYONE] {
+            assert_eq!(
+                quorum.answered(&members).to_set(),
+                quorum.count_answered(&members).to_set()
+            );
+        }
+    }
+
+    #[test]
+    fn quorum_from_str() {
+        for quorum in &[
+            "anyone",
+            "everyone",
+            "at-least:3",
+            "at-least:75%",
+            "exactly:1",
+        ] {
+            assert_eq!(quorum.parse::<Quorum>().unwrap().to_string(), *quorum);
+        }
+        assert_eq!(
+            "at-least:101%".parse::<Quorum>(),
+            Err("Quorum percent 101 is over 100".to_string())
+        );
+        for quorum in &["at-least:0", "exactly:0"] {
+            assert_eq!(
+                quorum.parse::<Quorum>(),
+                Err("Quorum size 0 is never met".to_string())
+            );
+        }
+        assert_eq!(
+            "exactly:one".parse::<Quorum>(),
+            Err("Quorum size is not a number: one".to_string())
+        );
+        assert_eq!(
+            "most".parse::<Quorum>(),
+            Err("Quorum is not recognized: most".to_string())
+        );
+    }
+}
@@ -0,0 +1,305 @@
+use crate::types::Group;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
+
+/// Pairs of questions in the report, when not given
+pub(crate) const TOP_PAIRS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            case => Err(format!("Report format is not recognized: {}", case)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct QuestionStats {
+    pub question: char,
+    /// People that answered the question
+    pub people: usize,
+    /// Groups where anyone answered the question
+    pub groups: usize,
+}
+
+/// Two questions that the same people answered, `first` goes before `second`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct PairStats {
+    pub first: char,
+    pub second: char,
+    pub people: usize,
+}
+
+/// Statistics of the answers across every group
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Report {
+    pub groups: usize,
+    pub people: usize,
+    /// Every question somebody answered, in alphabetical order
+    pub questions: Vec<QuestionStats>,
+    /// Number of groups of every size
+    pub group_sizes: BTreeMap<usize, usize>,
+    /// Questions most people answered, more than one on a tie
+    pub most_common: Vec<char>,
+    /// Questions fewest people answered, only questions somebody answered count
+    pub least_common: Vec<char>,
+    /// Pairs most people answered both of, the most common first
+    pub top_pairs: Vec<PairStats>,
+}
+
+/// Quote the value if it has a separator, quote or line break in it
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl Report {
+    /// Up to `top_pairs` pairs are kept
+    pub fn new(groups: &[Group], top_pairs: usize) -> Self {
+        let mut questions: BTreeMap<char, QuestionStats> = BTreeMap::new();
+        let mut group_sizes = BTreeMap::new();
+        let mut pairs: BTreeMap<(char, char), usize> = BTreeMap::new();
+
+        for group in groups {
+            *group_sizes.entry(group.members.len()).or_insert(0) += 1;
+
+            let mut group_questions = BTreeSet::new();
+            for person in &group.members {
+                let answered = person.answers().questions();
+                for (index, question) in answered.iter().enumerate() {
+                    questions
+                        .entry(*question)
+                        .or_insert(QuestionStats {
+                            question: *question,
+                            people: 0,
+                            groups: 0,
+                        })
+                        .people += 1;
+                    group_questions.insert(*question);
+
+                    for other in &answered[index + 1..] {
+                        *pairs.entry((*question, *other)).or_insert(0) += 1;
+                    }
+                }
+            }
+            for question in group_questions {
+                questions.get_mut(&question).expect("Counted above").groups += 1;
+            }
+        }
+
+        let questions: Vec<QuestionStats> = questions.into_values().collect();
+        let people_counts = questions.iter().map(|stats| stats.people);
+        let with_people = |people: Option<usize>| -> Vec<char> {
+            questions
+                .iter()
+                .filter(|stats| Some(stats.people) == people)
+                .map(|stats| stats.question)
+                .collect()
+        };
+
+        let mut top: Vec<PairStats> = pairs
+            .into_iter()
+            .map(|((first, second), people)| PairStats {
+                first,
+                second,
+                people,
+            })
+            .collect();
+        // Stable sort keeps pairs with the same count in alphabetical order
+        top.sort_by_key(|pair| std::cmp::Reverse(pair.people));
+        top.truncate(top_pairs);
+
+        Report {
+            groups: groups.len(),
+            people: groups.iter().map(|group| group.members.len()).sum(),
+            most_common: with_people(people_counts.clone().max()),
+            least_common: with_people(people_counts.min()),
+            questions,
+            group_sizes,
+            top_pairs: top,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Report is always serializable") + "\n"
+    }
+
+    /// One statistic per row: what it is, what it is about and its value.
+    ///
+    /// Keys of pairs are both questions separated by a space, e.g. "a b"
+    pub fn to_csv(&self) -> String {
+        let mut rows: Vec<(&str, String, usize)> = vec![
+            ("groups", String::new(), self.groups),
+            ("people", String::new(), self.people),
+        ];
+
+        for stats in &self.questions {
+            rows.push(("question_people", stats.question.to_string(), stats.people));
+            rows.push(("question_groups", stats.question.to_string(), stats.groups));
+        }
+        for (size, groups) in &self.group_sizes {
+            rows.push(("group_size", size.to_string(), *groups));
+        }
+        let people = |question: &char| {
+            self.questions
+                .iter()
+                .find(|stats| stats.question == *question)
+                .map_or(0, |stats| stats.people)
+        };
+        for question in &self.most_common {
+            rows.push(("most_common", question.to_string(), people(question)));
+        }
+        for question in &self.least_common {
+            rows.push(("least_common", question.to_string(), people(question)));
+        }
+        for pair in &self.top_pairs {
+            rows.push((
+                "pair",
+                format!("{} {}", pair.first, pair.second),
+                pair.people,
+            ));
+        }
+
+        std::iter::once("statistic,key,value".to_string())
+            .chain(rows.into_iter().map(|(statistic, key, value)| {
+                format!("{},{},{}", statistic, csv_escape(&key), value)
+            }))
+            .map(|row| row + "\n")
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::parse_groups;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    /// Example groups of the puzzle
+    fn report(top_pairs: usize) -> Report {
+        let groups = parse_groups("abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb\n").unwrap();
+
+        Report::new(&groups, top_pairs)
+    }
+
+    #[test]
+    fn report_of_example() {
+        let report = report(2);
+
+        assert_eq!((report.groups, report.people), (5, 11));
+        assert_eq!(
+            report.questions,
+            vec![
+                QuestionStats {
+                    question: 'a',
+                    people: 8,
+                    groups: 4
+                },
+                QuestionStats {
+                    question: 'b',
+                    people: 4,
+                    groups: 4
+                },
+                QuestionStats {
+                    question: 'c',
+                    people: 3,
+                    groups: 3
+                },
+            ]
+        );
+        assert_eq!(
+            report.group_sizes,
+            vec![(1, 2), (2, 1), (3, 1), (4, 1)]
+                .into_iter()
+                .collect::<BTreeMap<usize, usize>>()
+        );
+        assert_eq!(report.most_common, vec!['a']);
+        assert_eq!(report.least_common, vec!['c']);
+        assert_eq!(
+            report.top_pairs,
+            vec![
+                PairStats {
+                    first: 'a',
+                    second: 'b',
+                    people: 2
+                },
+                PairStats {
+                    first: 'a',
+                    second: 'c',
+                    people: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn report_to_csv() {
+        assert_eq!(
+            report(1).to_csv(),
+            indoc! {"
+                statistic,key,value
+                groups,,5
+                people,,11
+                question_people,a,8
+                question_groups,a,4
+                question_people,b,4
+                question_groups,b,4
+                question_people,c,3
+                question_groups,c,3
+                group_size,1,2
+                group_size,2,1
+                group_size,3,1
+                group_size,4,1
+                most_common,a,8
+                least_common,c,3
+                pair,a b,2
+            "}
+        );
+    }
+
+    #[test]
+    fn escape_csv() {
+        assert_eq!(csv_escape("a b"), "a b");
+        assert_eq!(csv_escape(","), "\",\"");
+        assert_eq!(csv_escape("\" ,"), "\"\"\" ,\"");
+    }
+
+    #[test]
+    fn report_to_json() {
+        let json: serde_json::Value = serde_json::from_str(&report(1).to_json()).unwrap();
+
+        assert_eq!(json["questions"][1]["people"], 4);
+        assert_eq!(json["group_sizes"]["1"], 2);
+        assert_eq!(json["most_common"], serde_json::json!(["a"]));
+        assert_eq!(
+            json["top_pairs"],
+            serde_json::json!([{"first": "a", "second": "b", "people": 2}])
+        );
+    }
+
+    #[test]
+    fn report_of_nothing() {
+        let report = Report::new(&[], TOP_PAIRS);
+
+        assert_eq!(report.people, 0);
+        assert!(report.most_common.is_empty() && report.least_common.is_empty());
+        assert!(report.top_pairs.is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+use crate::answers::Answers;
+use crate::quorum::Quorum;
+use std::str::FromStr;
+
+/// Questions one person answered "yes" to, one lowercase letter per question,
+/// letters outside a-z fall back to a set, see `Answers::new`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Person {
+    text: String,
+    answers: Answers,
+}
+
+impl Person {
+    /// Line as it was given
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn answers(&self) -> &Answers {
+        &self.answers
+    }
+}
+
+impl FromStr for Person {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("Person has no answers".to_string());
+        }
+        match s.chars().find(|question| !question.is_lowercase()) {
+            Some(question) => Err(format!(
+                "{} has answer {:?} that is not a lowercase letter",
+                s, question
+            )),
+            None => Ok(Person {
+                text: s.to_string(),
+                answers: Answers::new(s),
+            }),
+        }
+    }
+}
+
+/// People that travel together, one person per line
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Group {
+    pub members: Vec<Person>,
+    /// Line of the first member, lines start from 1
+    pub line: usize,
+}
+
+impl Group {
+    /// Answers of every member, in the input order
+    pub fn answers(&self) -> Vec<&Answers> {
+        self.members.iter().map(Person::answers).collect()
+    }
+
+    /// Who answered and who did not, for every question anyone in the group answered,
+    /// in alphabetical order
+    pub fn question_members(&self) -> Vec<QuestionMembers> {
+        Quorum::ANYONE
+            .answered(&self.answers())
+            .questions()
+            .into_iter()
+            .map(|question| {
+                let (answered, missing) = (0..self.members.len())
+                    .partition(|index| self.members[*index].answers().contains(question));
+                QuestionMembers {
+                    question,
+                    answered,
+                    missing,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Members of a group that answered a question and that did not, as indexes of `Group::members`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct QuestionMembers {
+    pub question: char,
+    pub answered: Vec<usize>,
+    pub missing: Vec<usize>,
+}
+
+impl QuestionMembers {
+    /// Somebody answered the question, but not everyone
+    pub fn is_partial(&self) -> bool {
+        !self.answered.is_empty() && !self.missing.is_empty()
+    }
+}
+
+/// Groups are separated by blank lines, any number of them, lines may end with CRLF.
+///
+/// Example error: "Group 3 on line 12: abC has answer 'C' that is not a lowercase letter"
+pub(crate) fn parse_groups(text: &str) -> Result<Vec<Group>, String> {
+    let mut groups: Vec<Group> = vec![];
+    let mut group = Group::default();
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let person = line.trim();
+
+        if person.is_empty() {
+            if !group.members.is_empty() {
+                groups.push(group);
+                group = Group::default();
+            }
+            continue;
+        }
+        if group.members.is_empty() {
+            group.line = line_number;
+        }
+
+        let person = person.parse().map_err(|err| {
+            format!(
+                "Group {} on line {}: {}",
+                groups.len() + 1,
+                line_number,
+                err
+            )
+        })?;
+        group.members.push(person);
+    }
+    if !group.members.is_empty() {
+        groups.push(group);
+    }
+
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn shape(groups: &[Group]) -> Vec<(usize, Vec<&str>)> {
+        groups
+            .iter()
+            .map(|group| (group.line, group.members.iter().map(Person::text).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn groups_of_example() {
+        let groups = parse_groups("abc\n\na\nb\nc\n\nab\nac\n\na\na\na\na\n\nb\n").unwrap();
+
+        assert_eq!(
+            shape(&groups),
+            vec![
+                (1, vec!["abc"]),
+                (3, vec!["a", "b", "c"]),
+                (7, vec!["ab", "ac"]),
+                (10, vec!["a", "a", "a", "a"]),
+                (15, vec!["b"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn crlf_and_many_blank_lines() {
+        let groups = parse_groups("\r\n\r\nab\r\nac\r\n\r\n \r\n\r\nb\r\n\r\n\r\n").unwrap();
+
+        assert_eq!(shape(&groups), vec![(3, vec!["ab", "ac"]), (8, vec!["b"])]);
+        assert_eq!(parse_groups(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn question_members() {
+        let groups = parse_groups("ab\nac\nabc\n").unwrap();
+        let members = groups[0].question_members();
+
+        assert_eq!(
+            members,
+            vec![
+                QuestionMembers {
+                    question: 'a',
+                    answered: vec![0, 1, 2],
+                    missing: vec![],
+                },
+                QuestionMembers {
+                    question: 'b',
+                    answered: vec![0, 2],
+                    missing: vec![1],
+                },
+                QuestionMembers {
+                    question: 'c',
+                    answered: vec![1, 2],
+                    missing: vec![0],
+                },
+            ]
+        );
+        assert_eq!(
+            members
+                .iter()
+                .filter(|members| members.is_partial())
+                .map(|members| members.question)
+                .collect::<String>(),
+            "bc"
+        );
+        assert_eq!(Group::default().question_members(), vec![]);
+    }
+
+    #[test]
+    fn bad_answers() {
+        assert_eq!(
+            parse_groups("abc\n\na\nb\n\nab\naCb\n"),
+            Err("Group 3 on line 7: aCb has answer 'C' that is not a lowercase letter".to_string())
+        );
+        assert_eq!(
+            parse_groups("ab c\n"),
+            Err(
+                "Group 1 on line 1: ab c has answer ' ' that is not a lowercase letter".to_string()
+            )
+        );
+        assert_eq!(
+            parse_groups("a1\n"),
+            Err("Group 1 on line 1: a1 has answer '1' that is not a lowercase letter".to_string())
+        );
+    }
+
+    #[test]
+    fn answers_outside_a_z_fall_back_to_set() {
+        let groups = parse_groups("ab\n\nxé\n").unwrap();
+
+        assert!(matches!(groups[0].members[0].answers(), Answers::Bits(_)));
+        assert!(matches!(groups[1].members[0].answers(), Answers::Set(_)));
+        assert_eq!(groups[1].members[0].answers().questions(), vec!['x', 'é']);
+    }
+}